@@ -2,10 +2,15 @@ use anyhow::Result;
 use clap::{Arg, ArgMatches, Command};
 
 use shellclear::clearer::Clearer;
-use shellclear::{config::Config, engine, Emojis, ShellContext};
+use shellclear::{
+    config::Config, detector::sequence::TYPED_PASSWORD_ID, dialog, engine, group_by_user,
+    settings::Settings, Emojis, ShellContext,
+};
+
+use super::{filter, strict};
 
 pub fn command() -> Command<'static> {
-    Command::new("clear")
+    filter::args(Command::new("clear"))
         .about("Remove or mask the findings from shell history")
         .arg(
             Arg::new("backup")
@@ -19,17 +24,45 @@ pub fn command() -> Command<'static> {
                 .help("Remove history that contains secrets")
                 .takes_value(false),
         )
+        .arg(
+            Arg::new("mask")
+                .long("mask")
+                .help("Mask the secrets even when the clear.remove setting is on")
+                .conflicts_with("remove")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::new("include-placeholders")
+                .long("include-placeholders")
+                .help("Include findings with empty, placeholder or variable reference secrets")
+                .takes_value(false),
+        )
 }
 
 pub fn run(
     matches: &ArgMatches,
-    shells_context: &Vec<ShellContext>,
+    shells_context: &[ShellContext],
     config: &Config,
+    settings: &Settings,
 ) -> Result<shellclear::data::CmdExit> {
-    let en = engine::PatternsEngine::with_config(config)?;
+    if matches.is_present("strict") {
+        if let Some(exit) = strict::check(config) {
+            return Ok(exit);
+        }
+    }
+
+    let remove =
+        matches.is_present("remove") || (settings.clear.remove && !matches.is_present("mask"));
+    let backup = matches.is_present("backup") || settings.clear.backup;
+
+    let en = engine::PatternsEngine::with_config(config)?
+        .with_placeholders(matches.is_present("include-placeholders"))
+        .with_filter(filter::from_matches(matches)?)
+        .with_mask(&settings.mask)
+        .with_correlation(&settings.correlation);
 
     for shell_context in shells_context {
-        if matches.is_present("backup") {
+        if backup {
             match shell_context.backup() {
                 Ok(path) => log::debug!("history backup successful: {}", path),
                 Err(e) => {
@@ -45,13 +78,34 @@ pub fn run(
         }
     }
 
-    let commands = en.find_history_commands_from_shell_list(shells_context)?;
-
     let emojis = Emojis::default();
 
-    Clearer::write_findings(shells_context, &commands, matches.is_present("remove"))?;
+    // the history files of a shell are merged, so each user is cleared apart
+    let mut sensitive_commands = vec![];
+    for (user, contexts) in group_by_user(shells_context) {
+        let mut commands = en.find_history_commands_from_shell_list(&contexts)?;
 
-    let sensitive_commands = commands.get_commands_with_secrets();
+        // passwords typed as commands are useless in the history, offer to remove
+        // them even when the other findings are only masked
+        let typed_passwords = commands.count_commands_with_detection(TYPED_PASSWORD_ID);
+        let owner = user
+            .map(|user| format!(" of user {user}"))
+            .unwrap_or_default();
+        if typed_passwords > 0
+            && !remove
+            && dialog::confirm(&format!(
+                "found {typed_passwords} passwords typed as commands{owner}. remove them from \
+                 history?"
+            ))
+            .is_ok()
+        {
+            commands.remove_commands_with_detection(TYPED_PASSWORD_ID);
+        }
+
+        Clearer::write_findings(&contexts, &commands, remove)?;
+
+        sensitive_commands.extend(commands.get_commands_with_secrets());
+    }
 
     if sensitive_commands.is_empty() {
         return Ok(shellclear::data::CmdExit {
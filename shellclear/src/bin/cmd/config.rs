@@ -1,13 +1,31 @@
+use std::{fs, path::Path, str::FromStr};
+
 use anyhow::Result;
+use chrono::Local;
 use clap::{Arg, ArgMatches, Command};
-use shellclear::{config::Config, data::Detection, dialog};
+use console::style;
+use shellclear::{
+    config::Config,
+    data::{Detection, IgnoreRule},
+    dialog,
+    importer::{self, ImportFormat},
+    linter,
+    packs::Packs,
+    registry::PatternRegistry,
+    settings::Settings,
+    tester,
+};
 
-use crate::engine::SENSITIVE_COMMANDS;
+use regex::Regex;
+use shellclear::engine::available_patterns;
 
 pub fn command() -> Command<'static> {
     Command::new("config")
         .about("Create custom configuration")
         .subcommand(Command::new("validate").about("Validate configuration file."))
+        .subcommand(
+            Command::new("show").about("Show the effective settings and where they come from"),
+        )
         .subcommand(
             Command::new("delete")
                 .about("Delete configuration file.")
@@ -18,16 +36,202 @@ pub fn command() -> Command<'static> {
                         .takes_value(false),
                 ),
         )
-        .subcommand(Command::new("ignores").about("Manage ignores patterns."))
+        .subcommand(
+            Command::new("ignores")
+                .about("Manage ignores patterns.")
+                .subcommand(
+                    Command::new("add")
+                        .about("Ignore all the findings of a pattern")
+                        .arg(Arg::new("id").required(true).help("Pattern id")),
+                )
+                .subcommand(
+                    Command::new("remove")
+                        .about("Stop ignoring the findings of a pattern")
+                        .arg(Arg::new("id").required(true).help("Pattern id")),
+                )
+                .subcommand(Command::new("list").about("List the ignore rules")),
+        )
+        .subcommand(
+            Command::new("packs")
+                .about("Manage pattern packs.")
+                .arg_required_else_help(true)
+                .subcommand(Command::new("list").about("List the pattern packs"))
+                .subcommand(
+                    Command::new("enable")
+                        .about("Enable a pattern pack")
+                        .arg(Arg::new("name").required(true).help("Pack name")),
+                )
+                .subcommand(
+                    Command::new("disable")
+                        .about("Disable a pattern pack")
+                        .arg(Arg::new("name").required(true).help("Pack name")),
+                ),
+        )
+        .subcommand(
+            Command::new("profiles")
+                .about("Manage configuration profiles.")
+                .arg_required_else_help(true)
+                .subcommand(Command::new("list").about("List the profiles"))
+                .subcommand(
+                    Command::new("create")
+                        .about("Create a profile")
+                        .arg(Arg::new("name").required(true).help("Profile name")),
+                )
+                .subcommand(
+                    Command::new("copy")
+                        .about("Create a profile with the settings, ignores and packs of another")
+                        .arg(Arg::new("from").required(true).help("Source profile name"))
+                        .arg(Arg::new("to").required(true).help("New profile name")),
+                ),
+        )
+        .subcommand(
+            Command::new("patterns")
+                .about("Manage sensitive patterns.")
+                .arg_required_else_help(true)
+                .subcommand(
+                    Command::new("add")
+                        .about("Add a custom pattern")
+                        .arg(
+                            Arg::new("id")
+                                .long("id")
+                                .help("Unique pattern id")
+                                .required(true)
+                                .takes_value(true),
+                        )
+                        .arg(
+                            Arg::new("name")
+                                .long("name")
+                                .help("Pattern name shown in the findings")
+                                .required(true)
+                                .takes_value(true),
+                        )
+                        .arg(
+                            Arg::new("regex")
+                                .long("regex")
+                                .help("Regex that matches the sensitive command")
+                                .required(true)
+                                .takes_value(true),
+                        )
+                        .arg(
+                            Arg::new("group")
+                                .long("group")
+                                .help("Regex capture group of the secret")
+                                .default_value("0")
+                                .takes_value(true),
+                        ),
+                )
+                .subcommand(
+                    Command::new("remove")
+                        .about("Remove a custom pattern")
+                        .arg(Arg::new("id").required(true).help("Pattern id")),
+                )
+                .subcommand(
+                    Command::new("import")
+                        .about("Import the detection rules of another secret scanner")
+                        .arg(
+                            Arg::new("from")
+                                .long("from")
+                                .help("Rules file format")
+                                .possible_values(vec!["gitleaks", "trufflehog"])
+                                .ignore_case(true)
+                                .required(true)
+                                .takes_value(true),
+                        )
+                        .arg(
+                            Arg::new("file")
+                                .required(true)
+                                .help("gitleaks TOML or trufflehog YAML rules file"),
+                        )
+                        .arg(
+                            Arg::new("dry-run")
+                                .long("dry-run")
+                                .help("Print the converted patterns instead of writing them")
+                                .takes_value(false),
+                        ),
+                )
+                .subcommand(
+                    Command::new("test")
+                        .about("Run the examples and counter examples of the patterns")
+                        .arg(Arg::new("id").help("Test only the pattern with the given id")),
+                )
+                .subcommand(
+                    Command::new("list")
+                        .about("List the patterns of all the sources")
+                        .arg(
+                            Arg::new("effective")
+                                .long("effective")
+                                .help("Show only the patterns in use after overrides by id")
+                                .takes_value(false),
+                        ),
+                ),
+        )
 }
 
-pub fn run(subcommand_matches: &ArgMatches, config: &Config) -> Result<shellclear::data::CmdExit> {
+pub fn run(
+    subcommand_matches: &ArgMatches,
+    config: &Config,
+    settings: &Settings,
+) -> Result<shellclear::data::CmdExit> {
     match subcommand_matches.subcommand() {
         None => run_create_config(config),
         Some(tup) => match tup {
             ("validate", _subcommand_matches) => Ok(run_validate(config)),
+            ("show", _subcommand_matches) => Ok(run_show(settings)),
             ("delete", matches) => Ok(run_delete(config, matches.is_present("force"))?),
-            ("ignores", _matches) => Ok(run_ignore(config)?),
+            ("ignores", matches) => match matches.subcommand() {
+                None => Ok(run_ignore(config)?),
+                Some(("add", matches)) => run_ignores_add(config, matches.value_of("id").unwrap()),
+                Some(("remove", matches)) => {
+                    run_ignores_remove(config, matches.value_of("id").unwrap())
+                }
+                Some(("list", _)) => run_ignores_list(config),
+                _ => unreachable!(),
+            },
+            ("packs", matches) => match matches.subcommand() {
+                Some(("list", _)) => Ok(run_packs_list(config)),
+                Some(("enable", matches)) => {
+                    run_packs_enable(config, matches.value_of("name").unwrap(), true)
+                }
+                Some(("disable", matches)) => {
+                    run_packs_enable(config, matches.value_of("name").unwrap(), false)
+                }
+                _ => unreachable!(),
+            },
+            ("profiles", matches) => match matches.subcommand() {
+                Some(("list", _)) => run_profiles_list(config),
+                Some(("create", matches)) => {
+                    run_profiles_create(config, matches.value_of("name").unwrap())
+                }
+                Some(("copy", matches)) => run_profiles_copy(
+                    config,
+                    matches.value_of("from").unwrap(),
+                    matches.value_of("to").unwrap(),
+                ),
+                _ => unreachable!(),
+            },
+            ("patterns", matches) => match matches.subcommand() {
+                Some(("add", matches)) => run_patterns_add(
+                    config,
+                    matches.value_of("id").unwrap(),
+                    matches.value_of("name").unwrap(),
+                    matches.value_of("regex").unwrap(),
+                    matches.value_of("group").unwrap(),
+                ),
+                Some(("remove", matches)) => {
+                    run_patterns_remove(config, matches.value_of("id").unwrap())
+                }
+                Some(("import", matches)) => run_patterns_import(
+                    config,
+                    ImportFormat::from_str(&matches.value_of("from").unwrap().to_lowercase())?,
+                    Path::new(matches.value_of("file").unwrap()),
+                    matches.is_present("dry-run"),
+                ),
+                Some(("test", matches)) => run_patterns_test(config, matches.value_of("id")),
+                Some(("list", matches)) => {
+                    run_patterns_list(config, matches.is_present("effective"))
+                }
+                _ => unreachable!(),
+            },
             _ => unreachable!(),
         },
     }
@@ -62,31 +266,71 @@ fn run_validate(config: &Config) -> shellclear::data::CmdExit {
     let mut result: Vec<String> = vec![];
     let mut error_found = exitcode::OK;
 
-    match config.load_patterns_from_default_path() {
-        Ok(r) => result.push(format!(
-            "- found {} external sensitive patterns in path: {}",
-            r.len(),
+    if !config.sensitive_commands_path.exists() {
+        result.push(format!(
+            "- sensitive patterns file {} not found",
             config.sensitive_commands_path.display(),
-        )),
-        Err(e) => {
+        ));
+        error_found = exitcode::CONFIG;
+    }
+    for (source, path) in PatternRegistry::custom_sources(config) {
+        let issues = linter::lint_file(&path);
+        if issues.is_empty() {
+            let count = Config::load_patterns(&path).map_or(0, |p| p.len());
             result.push(format!(
-                "- sensitive patterns file {} is invalid. error {}",
-                config.sensitive_commands_path.display(),
-                e
+                "- found {} {} sensitive patterns in path: {}",
+                count,
+                source,
+                path.display(),
             ));
+        } else {
+            result.extend(issues.iter().map(|i| format!("- {i}")));
             error_found = exitcode::CONFIG;
         }
     }
+    let packs = Packs::with_config(config);
+    for pack in packs.enabled() {
+        result.push(format!(
+            "- found {} sensitive patterns in pack {}: {}",
+            pack.patterns.len(),
+            pack.label(),
+            pack.path.display(),
+        ));
+    }
+    let pack_issues = linter::lint_packs(&packs);
+    if !pack_issues.is_empty() {
+        result.extend(pack_issues.iter().map(|i| format!("- {i}")));
+        error_found = exitcode::CONFIG;
+    }
     match config.get_ignore_patterns() {
-        Ok(i) => result.push(format!(
-            "- found {} ignore patterns in path: {}",
-            i.len(),
-            config.sensitive_commands_path.display(),
-        )),
+        Ok(rules) => {
+            result.push(format!(
+                "- found {} ignore rules in path: {}",
+                rules.len(),
+                config.ignore_sensitive_path.display(),
+            ));
+            let today = Local::now().naive_local().date();
+            for rule in &rules {
+                if rule.is_empty() {
+                    result.push(format!(
+                        "- ignore rule {:?} has none of pattern_id, secret_fingerprint, \
+                         command_regex, shell and is not applied",
+                        rule.reason.as_deref().unwrap_or_default()
+                    ));
+                }
+                if let Some(expires) = rule.expires.filter(|_| rule.is_expired(today)) {
+                    result.push(format!(
+                        "- ignore rule {} expired on {} and is not applied",
+                        describe_ignore_rule(rule),
+                        expires
+                    ));
+                }
+            }
+        }
         Err(e) => {
             result.push(format!(
                 "- ignore file {} is invalid. error {}",
-                config.sensitive_commands_path.display(),
+                config.ignore_sensitive_path.display(),
                 e
             ));
             error_found = exitcode::CONFIG;
@@ -98,6 +342,477 @@ fn run_validate(config: &Config) -> shellclear::data::CmdExit {
     }
 }
 
+fn describe_ignore_rule(rule: &IgnoreRule) -> String {
+    let mut fields = vec![];
+    if let Some(id) = &rule.pattern_id {
+        fields.push(format!("pattern_id: {id}"));
+    }
+    if let Some(fingerprint) = &rule.secret_fingerprint {
+        fields.push(format!("secret_fingerprint: {fingerprint}"));
+    }
+    if let Some(regex) = &rule.command_regex {
+        fields.push(format!("command_regex: {regex}"));
+    }
+    if let Some(shell) = &rule.shell {
+        fields.push(format!("shell: {shell}"));
+    }
+    if let Some(reason) = &rule.reason {
+        fields.push(format!("reason: {reason}"));
+    }
+    format!("[{}]", fields.join(", "))
+}
+
+fn run_patterns_list(config: &Config, effective: bool) -> Result<shellclear::data::CmdExit> {
+    let registry = PatternRegistry::with_config(config)?;
+    let today = Local::now().naive_local().date();
+    let ignores = config
+        .get_ignore_patterns()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|r| r.is_pattern_only() && !r.is_expired(today))
+        .filter_map(|r| r.pattern_id)
+        .collect::<Vec<_>>();
+
+    let mut patterns = registry
+        .patterns()
+        .iter()
+        .map(|p| (p, None))
+        .collect::<Vec<_>>();
+    if !effective {
+        for pattern in registry.overridden() {
+            let by = registry
+                .patterns()
+                .iter()
+                .find(|p| p.detection.id == pattern.detection.id)
+                .map(|p| p.source);
+            patterns.push((pattern, by));
+        }
+    }
+
+    for (pattern, overridden_by) in &patterns {
+        let mut status = vec![pattern.source.to_string()];
+        status.push(
+            pattern
+                .path
+                .as_ref()
+                .map_or_else(|| "embedded".to_string(), |p| p.display().to_string()),
+        );
+        if ignores.contains(&pattern.detection.id) {
+            status.push("ignored".to_string());
+        }
+        if let Some(pack) = &pattern.pack {
+            status.push(format!("pack {pack}"));
+        }
+        if let Some(source) = pattern.overrides {
+            status.push(format!("overrides {source}"));
+        }
+        if let Some(source) = overridden_by {
+            status.push(format!("overridden by {source}"));
+        }
+        println!(
+            "{} {} [{}]\n    {}",
+            style(&pattern.detection.id).bold(),
+            pattern.detection.name,
+            status.join(", "),
+            style(&pattern.detection.test).dim()
+        );
+    }
+
+    Ok(shellclear::data::CmdExit {
+        code: exitcode::OK,
+        message: Some(format!("found {} patterns", patterns.len())),
+    })
+}
+
+fn load_custom_patterns(config: &Config) -> Result<Vec<Detection>> {
+    if config.sensitive_commands_path.exists() {
+        config.load_patterns_from_default_path()
+    } else {
+        Ok(vec![])
+    }
+}
+
+fn load_ignore_rules(config: &Config) -> Result<Vec<IgnoreRule>> {
+    if config.ignore_sensitive_path.exists() {
+        config.get_ignore_patterns()
+    } else {
+        Ok(vec![])
+    }
+}
+
+fn invalid_input(message: String) -> shellclear::data::CmdExit {
+    shellclear::data::CmdExit {
+        code: exitcode::DATAERR,
+        message: Some(message),
+    }
+}
+
+fn run_patterns_add(
+    config: &Config,
+    id: &str,
+    name: &str,
+    regex: &str,
+    group: &str,
+) -> Result<shellclear::data::CmdExit> {
+    if id.trim().is_empty() {
+        return Ok(invalid_input("pattern id can't be empty".to_string()));
+    }
+    let test = match Regex::new(regex) {
+        Ok(r) => r,
+        Err(e) => return Ok(invalid_input(format!("invalid regex {regex}. error {e}"))),
+    };
+    let secret_group = match group.parse::<u8>() {
+        Ok(g) if usize::from(g) < test.captures_len() => g,
+        _ => {
+            return Ok(invalid_input(format!(
+                "invalid group {group}. the regex has {} capture groups",
+                test.captures_len() - 1
+            )))
+        }
+    };
+
+    let patterns = load_custom_patterns(config)?;
+    if patterns.iter().any(|p| p.id == id) {
+        return Ok(invalid_input(format!(
+            "pattern {id} already exists in {}",
+            config.sensitive_commands_path.display()
+        )));
+    }
+    config.add_patterns(&[Detection::new(id, name, test, secret_group)])?;
+
+    Ok(shellclear::data::CmdExit {
+        code: exitcode::OK,
+        message: Some(format!(
+            "pattern {id} added to {}",
+            config.sensitive_commands_path.display()
+        )),
+    })
+}
+
+fn run_patterns_remove(config: &Config, id: &str) -> Result<shellclear::data::CmdExit> {
+    if config.remove_pattern(id)? == 0 {
+        return Ok(invalid_input(format!(
+            "pattern {id} not found in {}. built-in patterns can be disabled with `config \
+             ignores add {id}`",
+            config.sensitive_commands_path.display()
+        )));
+    }
+
+    Ok(shellclear::data::CmdExit {
+        code: exitcode::OK,
+        message: Some(format!(
+            "pattern {id} removed from {}",
+            config.sensitive_commands_path.display()
+        )),
+    })
+}
+
+fn run_ignores_add(config: &Config, id: &str) -> Result<shellclear::data::CmdExit> {
+    if !available_patterns(config)?.iter().any(|p| p.id == id) {
+        return Ok(invalid_input(format!(
+            "pattern {id} not found. list the patterns with `config patterns list`"
+        )));
+    }
+
+    let rules = load_ignore_rules(config)?;
+    if rules
+        .iter()
+        .any(|r| r.is_pattern_only() && r.pattern_id.as_deref() == Some(id))
+    {
+        return Ok(shellclear::data::CmdExit {
+            code: exitcode::OK,
+            message: Some(format!("pattern {id} is already ignored")),
+        });
+    }
+    config.add_ignore_rules(&[IgnoreRule::pattern(id)])?;
+
+    Ok(shellclear::data::CmdExit {
+        code: exitcode::OK,
+        message: Some(format!("pattern {id} ignored")),
+    })
+}
+
+fn run_ignores_remove(config: &Config, id: &str) -> Result<shellclear::data::CmdExit> {
+    let removed = config
+        .remove_ignore_rules(|r| r.is_pattern_only() && r.pattern_id.as_deref() == Some(id))?;
+    if removed == 0 {
+        return Ok(invalid_input(format!(
+            "pattern {id} not found in {}",
+            config.ignore_sensitive_path.display()
+        )));
+    }
+
+    Ok(shellclear::data::CmdExit {
+        code: exitcode::OK,
+        message: Some(format!("pattern {id} is no longer ignored")),
+    })
+}
+
+fn run_ignores_list(config: &Config) -> Result<shellclear::data::CmdExit> {
+    let rules = load_ignore_rules(config)?;
+    let today = Local::now().naive_local().date();
+    for rule in &rules {
+        if rule.is_expired(today) {
+            println!("{} {}", describe_ignore_rule(rule), style("expired").dim());
+        } else {
+            println!("{}", describe_ignore_rule(rule));
+        }
+    }
+
+    Ok(shellclear::data::CmdExit {
+        code: exitcode::OK,
+        message: Some(format!("found {} ignore rules", rules.len())),
+    })
+}
+
+fn run_show(settings: &Settings) -> shellclear::data::CmdExit {
+    for (key, value, origin) in settings.entries() {
+        println!(
+            "{}: {} {}",
+            style(key).bold(),
+            value,
+            style(format!("({origin})")).dim()
+        );
+    }
+
+    shellclear::data::CmdExit {
+        code: exitcode::OK,
+        message: None,
+    }
+}
+
+fn run_packs_list(config: &Config) -> shellclear::data::CmdExit {
+    let packs = Packs::with_config(config);
+    for pack in &packs.packs {
+        let mut status = vec![format!("{} patterns", pack.patterns.len())];
+        if !pack.include.is_empty() {
+            status.push(format!("includes {}", pack.include.join(", ")));
+        }
+        if !pack.enabled {
+            status.push("disabled".to_string());
+        }
+        println!(
+            "{} [{}]\n    {}",
+            style(pack.label()).bold(),
+            status.join(", "),
+            style(pack.path.display()).dim()
+        );
+    }
+    for (path, e) in &packs.errors {
+        println!("{} {}: {}", style("error").red(), path.display(), e);
+    }
+
+    shellclear::data::CmdExit {
+        code: if packs.errors.is_empty() {
+            exitcode::OK
+        } else {
+            exitcode::CONFIG
+        },
+        message: Some(format!("found {} packs", packs.packs.len())),
+    }
+}
+
+fn run_packs_enable(
+    config: &Config,
+    name: &str,
+    enable: bool,
+) -> Result<shellclear::data::CmdExit> {
+    if !Packs::with_config(config)
+        .packs
+        .iter()
+        .any(|p| p.name == name)
+    {
+        return Ok(invalid_input(format!(
+            "pack {name} not found. list the packs with `config packs list`"
+        )));
+    }
+
+    let mut packs_config = config.get_packs_config()?;
+    packs_config.disabled.retain(|n| n != name);
+    if !enable {
+        packs_config.disabled.push(name.to_string());
+    }
+    config.save_packs_config(&packs_config)?;
+
+    Ok(shellclear::data::CmdExit {
+        code: exitcode::OK,
+        message: Some(format!(
+            "pack {name} {}",
+            if enable { "enabled" } else { "disabled" }
+        )),
+    })
+}
+
+fn run_profiles_list(config: &Config) -> Result<shellclear::data::CmdExit> {
+    let profiles = config.list_profiles()?;
+    for name in &profiles {
+        if config.profile.as_ref() == Some(name) {
+            println!("{} {}", style(name).bold(), style("(active)").green());
+        } else {
+            println!("{name}");
+        }
+    }
+
+    Ok(shellclear::data::CmdExit {
+        code: exitcode::OK,
+        message: Some(format!("found {} profiles", profiles.len())),
+    })
+}
+
+fn run_profiles_create(config: &Config, name: &str) -> Result<shellclear::data::CmdExit> {
+    Ok(match config.create_profile(name) {
+        Ok(path) => shellclear::data::CmdExit {
+            code: exitcode::OK,
+            message: Some(format!(
+                "profile {name} created in path: {}. use it with `--profile {name}`",
+                path.display()
+            )),
+        },
+        Err(e) => invalid_input(e.to_string()),
+    })
+}
+
+fn run_profiles_copy(config: &Config, from: &str, to: &str) -> Result<shellclear::data::CmdExit> {
+    Ok(match config.copy_profile(from, to) {
+        Ok(path) => shellclear::data::CmdExit {
+            code: exitcode::OK,
+            message: Some(format!(
+                "profile {from} copied to {to} in path: {}",
+                path.display()
+            )),
+        },
+        Err(e) => invalid_input(e.to_string()),
+    })
+}
+
+fn run_patterns_import(
+    config: &Config,
+    format: ImportFormat,
+    file: &Path,
+    dry_run: bool,
+) -> Result<shellclear::data::CmdExit> {
+    let mut import = importer::import(format, &fs::read_to_string(file)?)?;
+
+    let patterns = load_custom_patterns(config)?;
+    import.patterns.retain(|p| {
+        let exists = patterns.iter().any(|existing| existing.id == p.id);
+        if exists {
+            import.warnings.push(format!(
+                "pattern {} skipped, the id already exists in {}",
+                p.id,
+                config.sensitive_commands_path.display()
+            ));
+        }
+        !exists
+    });
+    for warning in &import.warnings {
+        println!("{} {}", style("not mapped:").yellow(), warning);
+    }
+
+    if dry_run {
+        println!("{}", serde_yaml::to_string(&import.patterns)?);
+        if !import.ignores.is_empty() {
+            println!("{}", serde_yaml::to_string(&import.ignores)?);
+        }
+        return Ok(shellclear::data::CmdExit {
+            code: exitcode::OK,
+            message: Some(format!(
+                "converted {} patterns and {} ignore rules from {}",
+                import.patterns.len(),
+                import.ignores.len(),
+                file.display()
+            )),
+        });
+    }
+
+    // skip the rules that were imported before
+    let rules = load_ignore_rules(config)?;
+    import.ignores.retain(|rule| {
+        !rules.iter().any(|r| {
+            r.pattern_id == rule.pattern_id
+                && r.command_regex.as_ref().map(Regex::as_str)
+                    == rule.command_regex.as_ref().map(Regex::as_str)
+        })
+    });
+
+    let (pattern_count, ignore_count) = (import.patterns.len(), import.ignores.len());
+    config.add_patterns(&import.patterns)?;
+    if ignore_count > 0 {
+        config.add_ignore_rules(&import.ignores)?;
+    }
+
+    Ok(shellclear::data::CmdExit {
+        code: exitcode::OK,
+        message: Some(format!(
+            "imported {} patterns and {} ignore rules from {} to {}",
+            pattern_count,
+            ignore_count,
+            file.display(),
+            config.app_path.display()
+        )),
+    })
+}
+
+fn run_patterns_test(config: &Config, id: Option<&str>) -> Result<shellclear::data::CmdExit> {
+    let registry = PatternRegistry::with_config(config)?;
+    let patterns = registry
+        .patterns()
+        .iter()
+        .filter(|p| id.is_none_or(|id| p.detection.id == id))
+        .collect::<Vec<_>>();
+    if let (Some(id), true) = (id, patterns.is_empty()) {
+        return Ok(invalid_input(format!("pattern {id} not found")));
+    }
+
+    let mut passed = 0;
+    let mut failed = 0;
+    let mut untested = 0;
+    for pattern in patterns {
+        let results = tester::run_examples(&pattern.detection);
+        if results.is_empty() {
+            untested += 1;
+            continue;
+        }
+        for result in results {
+            if result.passed() {
+                passed += 1;
+                println!(
+                    "{} {} {}{}",
+                    style("PASS").green(),
+                    style(&result.pattern_id).bold(),
+                    result.text,
+                    if result.expected.is_none() {
+                        style(" (counter example)").dim()
+                    } else {
+                        style("")
+                    }
+                );
+            } else {
+                failed += 1;
+                println!(
+                    "{} {} {}\n    expected: {}\n    captured: {}",
+                    style("FAIL").red(),
+                    style(&result.pattern_id).bold(),
+                    result.text,
+                    result.expected.as_deref().unwrap_or("<no match>"),
+                    result.captured.as_deref().unwrap_or("<no match>"),
+                );
+            }
+        }
+    }
+
+    Ok(shellclear::data::CmdExit {
+        code: if failed == 0 {
+            exitcode::OK
+        } else {
+            exitcode::DATAERR
+        },
+        message: Some(format!(
+            "{passed} passed, {failed} failed, {untested} patterns without examples"
+        )),
+    })
+}
+
 fn run_delete(config: &Config, force: bool) -> Result<shellclear::data::CmdExit> {
     if !force
         && dialog::confirm(format!("Delete {} folder?", config.app_path.display()).as_str())
@@ -128,8 +843,11 @@ fn run_ignore(config: &Config) -> Result<shellclear::data::CmdExit> {
         config.init()?;
     }
 
-    // get all sensitive commands
-    let sensitive_patterns: Vec<Detection> = serde_yaml::from_str(SENSITIVE_COMMANDS)?;
+    // get all sensitive commands, including the custom ones
+    let sensitive_patterns = available_patterns(config)?
+        .into_iter()
+        .filter(|p| !p.id.is_empty())
+        .collect::<Vec<_>>();
 
     let (show_selections, show_ignores) =
         get_patter_ignore_multi_choice(config, &sensitive_patterns);
@@ -150,7 +868,16 @@ fn run_ignore(config: &Config) -> Result<shellclear::data::CmdExit> {
 
     log::debug!("selected ignores patterns: {:?}", selected_ides);
 
-    config.save_ignores_patterns(&selected_ides)?;
+    // keep the rules that ignore only some findings of a pattern
+    let mut rules = config
+        .get_ignore_patterns()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|r| !r.is_pattern_only())
+        .collect::<Vec<_>>();
+    rules.extend(selected_ides.iter().map(|id| IgnoreRule::pattern(id)));
+
+    config.save_ignores_patterns(&rules)?;
     Ok(shellclear::data::CmdExit {
         code: exitcode::OK,
         message: None,
@@ -162,7 +889,13 @@ fn get_patter_ignore_multi_choice(
     sensitive_patterns: &[Detection],
 ) -> (Vec<String>, Vec<String>) {
     // get current pattern ignores
-    let ignore_patterns = config.get_ignore_patterns().unwrap_or_default();
+    let ignore_patterns = config
+        .get_ignore_patterns()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(IgnoreRule::is_pattern_only)
+        .filter_map(|r| r.pattern_id)
+        .collect::<Vec<_>>();
 
     // filter ignores
     let show_selections = sensitive_patterns
@@ -183,8 +916,6 @@ fn get_patter_ignore_multi_choice(
 
 #[cfg(test)]
 mod test_cli_config {
-    use std::fs;
-
     use insta::assert_debug_snapshot;
     use regex::Regex;
     use tempdir::TempDir;
@@ -245,30 +976,153 @@ mod test_cli_config {
         let config = new_config(&temp_dir);
 
         config.init().unwrap();
-        config.save_ignores_patterns(&["id-3".to_string()]).unwrap();
+        config
+            .save_ignores_patterns(&[IgnoreRule::pattern("id-3")])
+            .unwrap();
 
         let patterns: Vec<Detection> = vec![
-            Detection {
-                test: Regex::new("test").unwrap(),
-                name: "test-1".to_string(),
-                id: "id-1".to_string(),
-                secret_group: 0,
-            },
-            Detection {
-                test: Regex::new("test").unwrap(),
-                name: "test-2".to_string(),
-                id: "id-2".to_string(),
-                secret_group: 0,
-            },
-            Detection {
-                test: Regex::new("test").unwrap(),
-                name: "test-3".to_string(),
-                id: "id-3".to_string(),
-                secret_group: 0,
-            },
+            Detection::new("id-1", "test-1", Regex::new("test").unwrap(), 0),
+            Detection::new("id-2", "test-2", Regex::new("test").unwrap(), 0),
+            Detection::new("id-3", "test-3", Regex::new("test").unwrap(), 0),
         ];
 
         assert_debug_snapshot!(get_patter_ignore_multi_choice(&config, &patterns));
         temp_dir.close().unwrap();
     }
+
+    #[test]
+    fn can_add_and_remove_patterns() {
+        let temp_dir = TempDir::new("config-app-patterns").unwrap();
+        let config = Config::with_custom_path(&temp_dir.path().join("app"));
+
+        assert_debug_snapshot!(
+            run_patterns_add(&config, "my_token", "My Token", "token=(\\w+)", "1")
+                .unwrap()
+                .code
+        );
+        assert_debug_snapshot!(
+            run_patterns_add(&config, "my_token", "My Token", "token=(\\w+)", "1")
+                .unwrap()
+                .code
+        );
+        assert_debug_snapshot!(
+            run_patterns_add(&config, "bad_group", "Bad", "token=(\\w+)", "2")
+                .unwrap()
+                .code
+        );
+        assert_debug_snapshot!(
+            run_patterns_add(&config, "bad_regex", "Bad", "token=(", "0")
+                .unwrap()
+                .code
+        );
+        assert_debug_snapshot!(config.load_patterns_from_default_path().unwrap());
+        assert_debug_snapshot!(run_patterns_remove(&config, "my_token").unwrap().code);
+        assert_debug_snapshot!(
+            run_patterns_remove(&config, "github_env_token")
+                .unwrap()
+                .code
+        );
+        assert_debug_snapshot!(config.load_patterns_from_default_path().unwrap().len());
+        temp_dir.close().unwrap();
+    }
+
+    #[test]
+    fn can_add_and_remove_ignores() {
+        let temp_dir = TempDir::new("config-app-ignores").unwrap();
+        let config = Config::with_custom_path(&temp_dir.path().join("app"));
+
+        run_patterns_add(&config, "my_token", "My Token", "token=(\\w+)", "1").unwrap();
+        assert_debug_snapshot!(run_ignores_add(&config, "my_token").unwrap().code);
+        assert_debug_snapshot!(run_ignores_add(&config, "github_env_token").unwrap().code);
+        assert_debug_snapshot!(run_ignores_add(&config, "not_found").unwrap().code);
+        assert_debug_snapshot!(config.get_ignore_patterns().unwrap());
+        assert_debug_snapshot!(
+            run_ignores_remove(&config, "github_env_token")
+                .unwrap()
+                .code
+        );
+        assert_debug_snapshot!(
+            run_ignores_remove(&config, "github_env_token")
+                .unwrap()
+                .code
+        );
+        assert_debug_snapshot!(config.get_ignore_patterns().unwrap());
+        temp_dir.close().unwrap();
+    }
+
+    #[test]
+    fn can_import_patterns() {
+        let temp_dir = TempDir::new("config-app-import").unwrap();
+        let config = Config::with_custom_path(&temp_dir.path().join("app"));
+        let file = temp_dir.path().join("gitleaks.toml");
+        fs::write(
+            &file,
+            r#"
+[[rules]]
+id = "hog-token"
+description = "Hog Token"
+regex = '''hog_token=([a-z0-9]{16})'''
+secretGroup = 1
+
+[rules.allowlist]
+regexes = ['''hog_token=0+''']
+"#,
+        )
+        .unwrap();
+
+        assert_debug_snapshot!(
+            run_patterns_import(&config, ImportFormat::Gitleaks, &file, false)
+                .unwrap()
+                .code
+        );
+        assert_debug_snapshot!(
+            run_patterns_import(&config, ImportFormat::Gitleaks, &file, false)
+                .unwrap()
+                .code
+        );
+        assert_debug_snapshot!(config
+            .load_patterns_from_default_path()
+            .unwrap()
+            .iter()
+            .map(|p| p.id.clone())
+            .collect::<Vec<_>>());
+        assert_debug_snapshot!(config.get_ignore_patterns().unwrap().len());
+        temp_dir.close().unwrap();
+    }
+
+    #[test]
+    fn can_enable_and_disable_packs() {
+        let temp_dir = TempDir::new("config-app-packs").unwrap();
+        let config = Config::with_custom_path(&temp_dir.path().join("app"));
+        fs::create_dir_all(&config.pattern_packs_path).unwrap();
+        fs::write(
+            config.pattern_packs_path.join("team.yaml"),
+            r###"
+name: team
+version: 1.0.0
+patterns:
+  - id: team_token
+    name: Team Token
+    test: TEAM_TOKEN=(\w+)
+    secret_group: 1
+"###,
+        )
+        .unwrap();
+
+        let pack_patterns = |config: &Config| {
+            PatternRegistry::with_config(config)
+                .unwrap()
+                .patterns()
+                .iter()
+                .filter_map(|p| p.pack.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_debug_snapshot!(pack_patterns(&config));
+        assert_debug_snapshot!(run_packs_enable(&config, "team", false).unwrap().code);
+        assert_debug_snapshot!(pack_patterns(&config));
+        assert_debug_snapshot!(run_packs_enable(&config, "team", true).unwrap().code);
+        assert_debug_snapshot!(run_packs_enable(&config, "not-found", true).unwrap().code);
+        assert_debug_snapshot!(config.get_packs_config().unwrap());
+        temp_dir.close().unwrap();
+    }
 }
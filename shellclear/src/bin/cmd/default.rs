@@ -1,7 +1,9 @@
 use clap::{crate_name, crate_version, AppSettings, Arg, Command};
 
+use super::filter;
+
 pub fn command() -> Command<'static> {
-    Command::new(crate_name!())
+    filter::args(Command::new(crate_name!()))
         .version(crate_version!())
         .setting(AppSettings::ArgRequiredElseHelp)
         .about("Secure shell commands")
@@ -34,6 +36,21 @@ pub fn command() -> Command<'static> {
                 .help("Show sensitive findings summary for MOTD")
                 .takes_value(false),
         )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .help("Fail when custom patterns are invalid instead of skipping them")
+                .global(true)
+                .takes_value(false),
+        )
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .help("Use a profile of the config folder. defaults to SHELLCLEAR_PROFILE")
+                .value_name("NAME")
+                .global(true)
+                .takes_value(true),
+        )
         .arg(
             Arg::new("config-dir")
                 .long("config-dir")
@@ -41,4 +58,47 @@ pub fn command() -> Command<'static> {
                 .value_name("CFG_DIR_PATH")
                 .takes_value(true),
         )
+        .arg(
+            Arg::new("home")
+                .long("home")
+                .help("Scan the shell history files of this home directory")
+                .value_name("HOME_DIR_PATH")
+                .global(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("root")
+                .long("root")
+                .help(
+                    "Scan the history files of a mounted disk image, chroot or container rootfs. \
+                     the home directory is resolved inside the root",
+                )
+                .value_name("ROOT_DIR_PATH")
+                .global(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("all-users")
+                .long("all-users")
+                .help("Scan the home directories of all the users of the passwd file")
+                .conflicts_with("home")
+                .global(true)
+                .takes_value(false),
+        )
+        .arg(
+            Arg::new("passwd")
+                .long("passwd")
+                .help("Read the users of --all-users from this file. defaults to /etc/passwd in the root")
+                .value_name("PASSWD_PATH")
+                .requires("all-users")
+                .global(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("state-dir")
+                .long("state-dir")
+                .help("Set the backups and stash directory path")
+                .value_name("STATE_DIR_PATH")
+                .takes_value(true),
+        )
 }
@@ -0,0 +1,58 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use clap::{Arg, ArgMatches, Command};
+use shellclear::data::{FindingFilter, Severity};
+
+/// Add the severity and tags filter arguments to the command
+pub fn args(command: Command<'static>) -> Command<'static> {
+    command
+        .arg(
+            Arg::new("min-severity")
+                .long("min-severity")
+                .help("Report only findings with at least the given severity")
+                .possible_values(vec!["low", "medium", "high", "critical"])
+                .ignore_case(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("tags")
+                .long("tags")
+                .help("Report only findings with one of the given tags or categories")
+                .value_name("TAG")
+                .use_value_delimiter(true)
+                .multiple_values(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("exclude-tags")
+                .long("exclude-tags")
+                .help("Skip findings with one of the given tags or categories")
+                .value_name("TAG")
+                .use_value_delimiter(true)
+                .multiple_values(true)
+                .takes_value(true),
+        )
+}
+
+/// Create the findings filter from the arguments
+///
+/// # Errors
+///
+/// Will return `Err` when the severity is invalid
+pub fn from_matches(matches: &ArgMatches) -> Result<FindingFilter> {
+    let values = |name: &str| {
+        matches
+            .values_of(name)
+            .map(|v| v.map(ToString::to_string).collect::<Vec<_>>())
+            .unwrap_or_default()
+    };
+    Ok(FindingFilter {
+        min_severity: matches
+            .value_of("min-severity")
+            .map(|s| Severity::from_str(&s.to_lowercase()))
+            .transpose()?,
+        tags: values("tags"),
+        exclude_tags: values("exclude-tags"),
+    })
+}
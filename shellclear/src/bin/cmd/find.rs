@@ -1,40 +1,261 @@
+use std::{path::Path, str::FromStr};
+
 use anyhow::Result;
 use clap::{Arg, ArgMatches, Command};
 use console::style;
 
 use shellclear::{
     config::Config,
+    data::{Baseline, FormatCheck},
     engine,
     exporter::{Exporter, Table, Text},
+    fingerprint, git, group_by_user, image,
+    settings::{OutputFormat, Settings},
     Emojis, ShellContext,
 };
 
+use super::{filter, strict};
+
 pub fn command() -> Command<'static> {
-    Command::new("find").about("Find sensitive commands").arg(
-        Arg::new("format")
-            .long("format")
-            .help("Finding output format")
-            .possible_values(vec!["text", "table"])
-            .ignore_case(true)
-            .default_value("text")
-            .takes_value(true),
-    )
+    filter::args(Command::new("find"))
+        .about("Find sensitive commands")
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .help("Finding output format. defaults to the format setting")
+                .possible_values(vec!["text", "table"])
+                .ignore_case(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("image")
+                .long("image")
+                .help(
+                    "Scan the history files in the layers of a `docker save` or OCI image \
+                     tarball instead of the shell history",
+                )
+                .value_name("IMAGE_TAR")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("git")
+                .long("git")
+                .help(
+                    "Scan the history and shell rc files in all the commits of a local git \
+                     repository instead of the shell history",
+                )
+                .value_name("REPO")
+                .takes_value(true)
+                .conflicts_with("image"),
+        )
+        .arg(
+            Arg::new("include-placeholders")
+                .long("include-placeholders")
+                .help("Include findings with empty, placeholder or variable reference secrets")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::new("format-check")
+                .long("format-check")
+                .help("Show only findings with the given secret format validation result")
+                .possible_values(vec!["confirmed", "mismatch", "unchecked"])
+                .ignore_case(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("write-baseline")
+                .long("write-baseline")
+                .help(
+                    "Record the fingerprints of the current findings in a baseline file. defaults \
+                     to the baseline in the config folder",
+                )
+                .value_name("FILE")
+                .min_values(0)
+                .max_values(1)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("baseline")
+                .long("baseline")
+                .help(
+                    "Report only findings that are not in the baseline file. defaults to the \
+                     baseline in the config folder",
+                )
+                .value_name("FILE")
+                .min_values(0)
+                .max_values(1)
+                .takes_value(true)
+                .conflicts_with("write-baseline"),
+        )
+        .arg(
+            Arg::new("group-by")
+                .long("group-by")
+                .help("Group the findings by the secret to spot reused secrets")
+                .possible_values(vec!["secret"])
+                .ignore_case(true)
+                .takes_value(true),
+        )
+}
+
+/// A finding with where it was found
+struct Finding {
+    /// e.g. the history file path, listed by `--group-by secret`
+    source: String,
+    /// line of the command in a repository file
+    line: Option<usize>,
+    command: shellclear::data::Command,
+}
+
+impl Finding {
+    fn new(source: String, command: shellclear::data::Command) -> Self {
+        Self {
+            source,
+            line: None,
+            command,
+        }
+    }
 }
 
 pub fn run(
     matches: &ArgMatches,
-    shells_context: &Vec<ShellContext>,
+    shells_context: &[ShellContext],
     config: &Config,
+    settings: &Settings,
 ) -> Result<shellclear::data::CmdExit> {
-    let en = engine::PatternsEngine::with_config(config)?;
+    if matches.is_present("strict") {
+        if let Some(exit) = strict::check(config) {
+            return Ok(exit);
+        }
+    }
 
-    let sensitive_commands = en
-        .find_history_commands_from_shell_list(shells_context)?
-        .get_commands_with_secrets();
+    let mut en = engine::PatternsEngine::with_config(config)?
+        .with_placeholders(matches.is_present("include-placeholders"))
+        .with_filter(filter::from_matches(matches)?)
+        .with_mask(&settings.mask)
+        .with_correlation(&settings.correlation);
+    // the fingerprints of the groups are used in ignore rules, they must be
+    // stable between runs
+    if matches.is_present("group-by") {
+        en = en.with_fingerprint_salt(config.get_or_create_fingerprint_salt()?);
+    }
+
+    // findings are kept in sections per user, image history file or repository
+    // file version with a title, the history files of a shell are merged
+    let mut sections = vec![];
+    if let Some(image_path) = matches.value_of("image") {
+        let histories = match image::scan(Path::new(image_path)) {
+            Ok(histories) => histories,
+            Err(e) => {
+                return Ok(shellclear::data::CmdExit {
+                    code: exitcode::DATAERR,
+                    message: Some(format!("could not read image {image_path}. {e}")),
+                })
+            }
+        };
+        for history in histories {
+            let title = history.title();
+            // a malformed history file does not stop the scan of the other layers
+            let commands = match en.find_content_commands(&history.shell, &history.content) {
+                Ok(commands) => commands,
+                Err(e) => {
+                    log::warn!("could not read {}. err: {}", title, e);
+                    continue;
+                }
+            };
+            let findings = commands
+                .into_iter()
+                .filter(|c| !c.detections.is_empty())
+                .map(|c| Finding::new(title.clone(), c))
+                .collect::<Vec<_>>();
+            sections.push((Some(title), findings));
+        }
+    } else if let Some(repo_path) = matches.value_of("git") {
+        let files = match git::scan(Path::new(repo_path)) {
+            Ok(files) => files,
+            Err(e) => {
+                return Ok(shellclear::data::CmdExit {
+                    code: exitcode::DATAERR,
+                    message: Some(format!("could not read repository {repo_path}. {e}")),
+                })
+            }
+        };
+        // the findings of a file in a commit are next to each other
+        for finding in git::find(&en, &files) {
+            let title = finding.title();
+            let finding = Finding {
+                source: finding.source(),
+                line: Some(finding.line),
+                command: finding.command,
+            };
+            match sections.last_mut() {
+                Some((Some(last), findings)) if *last == title => findings.push(finding),
+                _ => sections.push((Some(title), vec![finding])),
+            }
+        }
+    } else {
+        for (user, contexts) in group_by_user(shells_context) {
+            // the source of a finding is the history file of its shell
+            let findings = en
+                .find_history_commands_from_shell_list(&contexts)?
+                .get_commands_with_secrets()
+                .into_iter()
+                .map(|c| {
+                    let source = contexts
+                        .iter()
+                        .find(|context| context.history.shell == c.shell_type)
+                        .map_or_else(
+                            || c.shell_type.to_string(),
+                            |context| context.history.path.clone(),
+                        );
+                    Finding::new(source, c)
+                })
+                .collect::<Vec<_>>();
+            sections.push((user.map(|user| format!("user {user}")), findings));
+        }
+    }
+
+    if matches.is_present("write-baseline") {
+        // the baseline has its own salt, so it matches on other machines
+        let salt = fingerprint::new_salt()?;
+        let fingerprints = sections
+            .iter()
+            .flat_map(|(_, findings)| findings)
+            .flat_map(|f| fingerprint::fingerprints(&salt, &f.command))
+            .collect::<Vec<_>>();
+        let baseline = Baseline {
+            salt: Some(salt),
+            fingerprints,
+        };
+        let path = config.save_baseline(matches.value_of("write-baseline"), &baseline)?;
+        return Ok(shellclear::data::CmdExit {
+            code: exitcode::OK,
+            message: Some(format!(
+                "baseline of {} sensitive commands written to: {}",
+                sections.iter().map(|(_, f)| f.len()).sum::<usize>(),
+                path.display()
+            )),
+        });
+    }
+
+    if matches.is_present("baseline") {
+        let baseline = config.get_baseline(matches.value_of("baseline"))?;
+        for (_, findings) in &mut sections {
+            findings.retain(|f| !fingerprint::is_in_baseline(&f.command, &baseline));
+        }
+    }
+
+    if let Some(format_check) = matches.value_of("format-check") {
+        let format_check = FormatCheck::from_str(&format_check.to_lowercase())?;
+        for (_, findings) in &mut sections {
+            findings.retain(|f| f.command.format_check == format_check);
+        }
+    }
+
+    sections.retain(|(_, findings)| !findings.is_empty());
 
     let emojis = Emojis::default();
 
-    if sensitive_commands.is_empty() {
+    if sections.is_empty() {
         return Ok(shellclear::data::CmdExit {
             code: exitcode::OK,
             message: Some(format!(
@@ -44,31 +265,80 @@ pub fn run(
         });
     };
 
-    let message = format!(
-        " {} found {} sensitive commands",
-        emojis.alarm,
-        sensitive_commands.len()
-    );
-    println!("\r\n{}\r\n", style(message).yellow());
+    let format = match matches.value_of("format") {
+        Some(format) => OutputFormat::from_str(&format.to_lowercase())?,
+        None => settings.format,
+    };
+    let exporter = match format {
+        OutputFormat::Table => Box::<Table>::default() as Box<dyn Exporter>,
+        OutputFormat::Text => Box::<Text>::default() as Box<dyn Exporter>,
+    };
 
-    let exporter = match matches.value_of("format") {
-        Some("table") => Box::<Table>::default() as Box<dyn Exporter>,
-        _ => Box::<Text>::default() as Box<dyn Exporter>,
+    // secrets are grouped across all the sections, so a secret reused by
+    // several users, files or commits is one group
+    let result = if matches.is_present("group-by") {
+        let findings = sections
+            .iter()
+            .flat_map(|(_, findings)| findings)
+            .map(|f| (f.source.as_str(), &f.command))
+            .collect::<Vec<_>>();
+        let groups = fingerprint::group_by_secret(findings.iter().copied());
+        let message = format!(
+            " {} found {} sensitive commands with {} distinct secrets",
+            emojis.alarm,
+            findings.len(),
+            groups.len()
+        );
+        println!("\r\n{}\r\n", style(message).yellow());
+        exporter.secret_groups(&groups)
+    } else {
+        sections.iter().try_for_each(|(title, findings)| {
+            if let Some(title) = title {
+                // the lines of a repository file, in the order of the findings
+                let lines = findings
+                    .iter()
+                    .filter_map(|f| f.line.map(|line| line.to_string()))
+                    .collect::<Vec<_>>();
+                let title = match lines.len() {
+                    0 => title.clone(),
+                    1 => format!("{} line {}", title, lines[0]),
+                    _ => format!("{} lines {}", title, lines.join(", ")),
+                };
+                println!("\r\n{}", style(title).bold().underlined());
+            }
+            let commands = findings
+                .iter()
+                .map(|f| f.command.clone())
+                .collect::<Vec<_>>();
+            let message = format!(
+                " {} found {} sensitive commands",
+                emojis.alarm,
+                commands.len()
+            );
+            println!("\r\n{}\r\n", style(message).yellow());
+            exporter.sensitive_data(&commands)
+        })
     };
 
-    Ok(
-        match exporter.sensitive_data(sensitive_commands.as_slice()) {
-            Ok(()) => shellclear::data::CmdExit {
-                code: exitcode::OK,
-                message: Some(
-                    "Run `shellclear clear` to clear command findings from your history"
-                        .to_string(),
-                ),
-            },
-            Err(e) => shellclear::data::CmdExit {
-                code: exitcode::OK,
-                message: Some(e.to_string()),
+    Ok(match result {
+        Ok(()) => shellclear::data::CmdExit {
+            code: if settings.fail_on_findings {
+                exitcode::DATAERR
+            } else {
+                exitcode::OK
             },
+            message: Some(if matches.is_present("image") {
+                "Rotate the secrets and rebuild the image without the history files".to_string()
+            } else if matches.is_present("git") {
+                "Rotate the secrets, they stay in the repository history until it is rewritten"
+                    .to_string()
+            } else {
+                "Run `shellclear clear` to clear command findings from your history".to_string()
+            }),
+        },
+        Err(e) => shellclear::data::CmdExit {
+            code: exitcode::OK,
+            message: Some(e.to_string()),
         },
-    )
+    })
 }
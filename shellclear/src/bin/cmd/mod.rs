@@ -1,5 +1,7 @@
 pub mod clear;
 pub mod config;
 pub mod default;
+pub mod filter;
 pub mod find;
 pub mod stash;
+pub mod strict;
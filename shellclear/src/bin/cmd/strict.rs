@@ -0,0 +1,20 @@
+use shellclear::{config::Config, data::CmdExit, linter};
+
+/// Fail when the custom patterns are invalid instead of skipping them
+pub fn check(config: &Config) -> Option<CmdExit> {
+    let issues = linter::lint_config(config);
+    if issues.is_empty() {
+        return None;
+    }
+    Some(CmdExit {
+        code: exitcode::CONFIG,
+        message: Some(format!(
+            "invalid custom patterns. run `shellclear config validate` for details\n\r{}",
+            issues
+                .iter()
+                .map(|i| format!("- {i}"))
+                .collect::<Vec<_>>()
+                .join("\n\r")
+        )),
+    })
+}
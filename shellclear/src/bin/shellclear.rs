@@ -1,13 +1,22 @@
-use std::process::exit;
+use std::{
+    path::{Path, PathBuf},
+    process::exit,
+};
 
 use anyhow::anyhow;
 use console::{style, Style};
 
-use shellclear::{config::Config, dialog, engine, init, Emojis, ShellContext};
+use shellclear::{
+    config::Config, dialog, engine, fingerprint, init, init_all_users, resolve_home,
+    resolve_in_root, settings::Settings, Emojis, ShellContext,
+};
 
 mod cmd;
 
 const DEFAULT_ERR_EXIT_CODE: i32 = 1;
+const PROFILE_ENV: &str = "SHELLCLEAR_PROFILE";
+/// users of `--all-users`, relative to the root
+const PASSWD_PATH: &str = "etc/passwd";
 
 pub const BANNER: &str = r#"
      _          _ _      _                 
@@ -31,26 +40,90 @@ fn main() {
     );
     env_logger::init_from_env(env);
 
-    let config = Config::from(matches.value_of("config-dir"));
+    let mut config = Config::from(matches.value_of("config-dir"));
+    if let Some(state_dir) = matches.value_of("state-dir") {
+        config = config.with_state_path(Path::new(state_dir));
+    }
+    // folders given on the command line are never migrated into
+    if matches.value_of("config-dir").is_none() && matches.value_of("state-dir").is_none() {
+        if let Some(home) = dirs::home_dir() {
+            if let Err(e) = config.migrate_legacy_folders(&home) {
+                log::warn!("could not migrate the legacy config folders. err: {}", e);
+            }
+        }
+    }
+    let profile = matches
+        .value_of("profile")
+        .map(ToString::to_string)
+        .or_else(|| std::env::var(PROFILE_ENV).ok())
+        .filter(|name| !name.is_empty());
+    if let Some(name) = profile {
+        config = match config.clone().with_profile(&name) {
+            Ok(c) => c,
+            Err(e) if matches.is_present("init-shell") => {
+                log::debug!("could not load profile. err: {}", e);
+                config
+            }
+            Err(e) => {
+                eprintln!("\r\n{}\r\n", style(e).red());
+                exit(exitcode::CONFIG)
+            }
+        };
+    }
+    let settings = match Settings::load(&config) {
+        Ok(s) => s,
+        // new shells must not get errors
+        Err(e) if matches.is_present("init-shell") => {
+            log::debug!("could not load settings. err: {}", e);
+            Settings::default()
+        }
+        Err(e) => {
+            eprintln!("\r\n{}\r\n", style(e).red());
+            exit(exitcode::CONFIG)
+        }
+    };
     // create app config to store state data
-    let shells_context = match init() {
+    let root = matches.value_of("root").map(Path::new);
+    let shells_context = if matches.is_present("all-users") {
+        let passwd = match (matches.value_of("passwd"), root) {
+            (Some(passwd), _) => Ok(PathBuf::from(passwd)),
+            (None, Some(root)) => resolve_in_root(root, Path::new(PASSWD_PATH)),
+            (None, None) => Ok(Path::new("/").join(PASSWD_PATH)),
+        };
+        passwd.and_then(|passwd| init_all_users(&config.state_path, &passwd, root))
+    } else {
+        resolve_home(matches.value_of("home").map(Path::new), root)
+            .and_then(|home| init(&config.state_path, &home, root))
+    };
+    let mut shells_context = match shells_context {
         Ok(s) => s,
         Err(e) => {
             log::error!("{}", e);
             exit(1)
         }
     };
+    shells_context.retain(|c| settings.scans(&c.history.shell));
 
     if matches.is_present("init-shell") {
         // In case of an error, we need to suppress the errors to make sure that when
         // new shell is open the users will not get any errors.
-        match engine::PatternsEngine::with_config(&config) {
+        let engine = cmd::filter::from_matches(&matches).and_then(|filter| {
+            Ok(engine::PatternsEngine::with_config(&config)?
+                .with_filter(filter)
+                .with_mask(&settings.mask)
+                .with_correlation(&settings.correlation))
+        });
+        match engine {
             Ok(engine) => {
                 let emojis = Emojis::default();
                 if let Ok(shell_commands) =
                     engine.find_history_commands_from_shell_list(&shells_context)
                 {
-                    let sensitive_commands = shell_commands.get_commands_with_secrets();
+                    let mut sensitive_commands = shell_commands.get_commands_with_secrets();
+                    // accepted findings in the baseline don't raise the alarm
+                    if let Ok(baseline) = config.get_baseline(None) {
+                        sensitive_commands.retain(|c| !fingerprint::is_in_baseline(c, &baseline));
+                    }
 
                     if sensitive_commands.is_empty() {
                         eprintln!(
@@ -74,7 +147,7 @@ fn main() {
         exit(0)
     }
 
-    if !matches.is_present("no_banner") {
+    if !matches.is_present("no_banner") && settings.banner {
         println!(
             "{}{}",
             style(BANNER).magenta(),
@@ -85,12 +158,14 @@ fn main() {
     let res = match matches.subcommand() {
         None => Err(anyhow!("command not found")),
         Some(tup) => match tup {
-            ("config", subcommand_matches) => cmd::config::run(subcommand_matches, &config),
+            ("config", subcommand_matches) => {
+                cmd::config::run(subcommand_matches, &config, &settings)
+            }
             ("find", subcommand_matches) => {
-                cmd::find::run(subcommand_matches, &shells_context, &config)
+                cmd::find::run(subcommand_matches, &shells_context, &config, &settings)
             }
             ("clear", subcommand_matches) => {
-                cmd::clear::run(subcommand_matches, &shells_context, &config)
+                cmd::clear::run(subcommand_matches, &shells_context, &config, &settings)
             }
             ("stash", subcommand_matches) => {
                 cmd::stash::run(subcommand_matches, select_shell(&shells_context))
@@ -119,13 +194,16 @@ fn main() {
     exit(exit_with)
 }
 
-fn select_shell(shell_contexts: &Vec<ShellContext>) -> &ShellContext {
+fn select_shell(shell_contexts: &[ShellContext]) -> &ShellContext {
     if shell_contexts.len() == 1 {
         return &shell_contexts[0];
     }
     let selections = shell_contexts
         .iter()
-        .map(|f| format!("{:?}     : {}", f.history.shell, f.history.path))
+        .map(|f| match &f.user {
+            Some(user) => format!("{:?} {}     : {}", f.history.shell, user, f.history.path),
+            None => format!("{:?}     : {}", f.history.shell, f.history.path),
+        })
         .collect::<Vec<_>>();
 
     match dialog::select("Pick your shell", &selections) {
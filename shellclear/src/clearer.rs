@@ -1,4 +1,4 @@
-use std::{fmt::Write, fs::write, time::Instant};
+use std::{fmt::Write, time::Instant};
 
 use anyhow::Result;
 
@@ -35,7 +35,7 @@ impl Clearer {
             }
 
             if !cleared_history.is_empty() {
-                write(&context.history.path, cleared_history)?;
+                context.save_history_content(&cleared_history)?;
                 log::debug!(
                     "time elapsed for backup existing file and write a new history to shell : {:?}",
                     start.elapsed()
@@ -57,7 +57,7 @@ mod tests {
 
     use super::*;
     use crate::{
-        data::{Command, Detection},
+        data::{Command, Detection, FormatCheck},
         shell::{History, Shell::Zshrc},
     };
 
@@ -69,6 +69,8 @@ mod tests {
                 file_name: "mock".to_string(),
                 path: dir.to_str().unwrap().into(),
             },
+            user: None,
+            root: None,
         }];
 
         // We test one finding with a secret and one without a secret
@@ -80,18 +82,28 @@ mod tests {
                 detections: vec![],
                 shell_type: Zshrc,
                 secrets: vec![],
+                spans: vec![],
+                jwt_claims: vec![],
+                format_check: FormatCheck::Unchecked,
+                incident: None,
+                fingerprints: vec![],
             },
             Command {
                 command: "should be removed".to_string(),
                 data: "should be removed".to_string(),
-                detections: vec![Detection {
-                    name: "mock".to_string(),
-                    secret_group: 0,
-                    id: "mock-id".to_string(),
-                    test: Regex::new("").unwrap(),
-                }],
+                detections: vec![Detection::new(
+                    "mock-id",
+                    "mock",
+                    Regex::new("").unwrap(),
+                    0,
+                )],
                 shell_type: Zshrc,
                 secrets: vec!["mock".to_string()],
+                spans: vec![],
+                jwt_claims: vec![],
+                format_check: FormatCheck::Unchecked,
+                incident: None,
+                fingerprints: vec![],
             },
         ];
 
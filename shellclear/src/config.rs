@@ -1,36 +1,141 @@
 use std::path::Path;
 use std::{fs, path::PathBuf};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 
-use crate::data::{Detection, ROOT_APP_FOLDER};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{
+    data::{Baseline, Detection, FlagRule, IgnoreRule, ROOT_APP_FOLDER},
+    fingerprint,
+    packs::PacksConfig,
+    state::{BACKUP_FOLDER, STASH_FOLDER},
+};
 
 const CONFIG_SENSITIVE_PATTERNS: &str = "sensitive-patterns.yaml";
 const CONFIG_IGNORES: &str = "ignores.yaml";
+const CONFIG_CLI_FLAGS: &str = "cli-flags.yaml";
+const CONFIG_URL_PARAMS: &str = "url-params.yaml";
+const CONFIG_FINGERPRINT_SALT: &str = "fingerprint-salt";
+const CONFIG_BASELINE: &str = "baseline.yaml";
+const CONFIG_PATTERN_PACKS: &str = "patterns.d";
+const CONFIG_PACKS: &str = "packs.yaml";
+const CONFIG_SETTINGS: &str = "config.yaml";
+const CONFIG_PROFILES: &str = "profiles";
+/// backups and stash folder inside a `--config-dir` folder
+const CONFIG_STATE: &str = "state";
+/// config folder in the home directory before the XDG layout
+const LEGACY_CONFIG_FOLDER: &str = ROOT_APP_FOLDER;
+/// backups and stash folder in the home directory before the XDG layout. it
+/// has the name of the project config folder, so only the backups and stash are
+/// migrated and a project config in the home directory stays
+const LEGACY_STATE_FOLDER: &str = PROJECT_CONFIG_FOLDER;
+/// entries of the legacy config folder that are migrated, anything else in
+/// `~/shellclear` is not ours
+const LEGACY_CONFIG_ENTRIES: &[&str] = &[
+    CONFIG_SENSITIVE_PATTERNS,
+    CONFIG_IGNORES,
+    CONFIG_CLI_FLAGS,
+    CONFIG_URL_PARAMS,
+    CONFIG_FINGERPRINT_SALT,
+    CONFIG_BASELINE,
+    CONFIG_PATTERN_PACKS,
+    CONFIG_PACKS,
+    CONFIG_SETTINGS,
+    CONFIG_PROFILES,
+];
+/// entries of the legacy state folder that are migrated
+const LEGACY_STATE_ENTRIES: &[&str] = &[BACKUP_FOLDER, STASH_FOLDER];
+const SYSTEM_CONFIG_FOLDER: &str = "/etc/shellclear";
+const PROJECT_CONFIG_FOLDER: &str = ".shellclear";
 const SENSITIVE_PATTERN_TEMPLATE: &str = r###"# External sensitive patterns file allows you you add a custom patterns to shellclear
 
-- name: Pattern Name
+- id: PATTERN_ID
+  name: Pattern Name
   test: <PATTERN REGEX>
   secret_group: 0
 "###;
-const IGNORES_SENSITIVE_PATTERN_TEMPLATE: &str = r###"# List of sensitive patterns id to ignore
+const IGNORES_SENSITIVE_PATTERN_TEMPLATE: &str = r###"# List of sensitive patterns id to ignore, or rules that ignore only the
+# findings matching all of: pattern_id, secret_fingerprint, command_regex, shell.
+# a rule can have a reason and an expires date (YYYY-MM-DD)
 
 - PATTERN_ID
 "###;
 
+const PROFILE_SETTINGS_TEMPLATE: &str = r###"# Settings of the profile, they override the settings of the config folder.
+# the profile has its own ignores.yaml and packs.yaml
+#
+# clear:
+#   remove: true
+#   backup: true
+# fail_on_findings: true
+# mask:
+#   percentage: 100
+"###;
+
+/// entry of the ignores file, a pattern id or a rule
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+enum IgnoreEntry {
+    PatternId(String),
+    Rule(IgnoreRule),
+}
+
 #[derive(Clone, Debug)]
 pub struct Config {
     pub app_path: PathBuf,
+    /// backups and stash of the shell history files
+    pub state_path: PathBuf,
     pub sensitive_commands_path: PathBuf,
     pub ignore_sensitive_path: PathBuf,
+    pub cli_flags_path: PathBuf,
+    pub url_params_path: PathBuf,
+    pub fingerprint_salt_path: PathBuf,
+    pub baseline_path: PathBuf,
+    /// directory of pattern packs
+    pub pattern_packs_path: PathBuf,
+    /// extra pack directories and disabled packs
+    pub packs_config_path: PathBuf,
+    /// `config.yaml` of the config folder
+    pub settings_path: PathBuf,
+    /// directory of the named profiles
+    pub profiles_path: PathBuf,
+    /// name of the selected profile
+    pub profile: Option<String>,
+    /// `config.yaml` of the selected profile
+    pub profile_settings_path: Option<PathBuf>,
+    /// `config.yaml` of `$XDG_CONFIG_HOME/shellclear`
+    pub user_settings_path: Option<PathBuf>,
+    /// settings shared by all the users of the machine
+    pub system_settings_path: PathBuf,
+    /// patterns shared by all the users of the machine
+    pub system_patterns_path: PathBuf,
+    /// patterns of the project in the current directory or its parents
+    pub project_patterns_path: Option<PathBuf>,
 }
 
 impl Default for Config {
+    /// `$XDG_CONFIG_HOME/shellclear` and `$XDG_STATE_HOME/shellclear`
     fn default() -> Self {
-        Self::with_custom_path(&dirs::home_dir().unwrap())
+        Self::with_paths(
+            &xdg_dir("XDG_CONFIG_HOME", ".config")
+                .unwrap()
+                .join(ROOT_APP_FOLDER),
+            &xdg_dir("XDG_STATE_HOME", ".local/state")
+                .unwrap()
+                .join(ROOT_APP_FOLDER),
+        )
     }
 }
 
+/// `$<var>` when it's an absolute path, `~/<default>` otherwise
+fn xdg_dir(var: &str, default: &str) -> Option<PathBuf> {
+    std::env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| dirs::home_dir().map(|home| home.join(default)))
+}
+
 impl From<Option<&str>> for Config {
     fn from(config_dir: Option<&str>) -> Self {
         match config_dir {
@@ -41,18 +146,181 @@ impl From<Option<&str>> for Config {
 }
 
 impl Config {
+    /// Config folder in the given directory, with the backups and stash in its
+    /// `state` folder
     #[allow(dead_code)]
     #[must_use]
     pub fn with_custom_path(root: &Path) -> Self {
         // todo check if we can remove this get_base_app_folder function
         let app_path = Self::get_base_app_folder(root);
+        Self::with_paths(&app_path, &app_path.join(CONFIG_STATE))
+    }
+
+    fn with_paths(app_path: &Path, state_path: &Path) -> Self {
+        let app_path = app_path.to_path_buf();
         Self {
+            state_path: state_path.to_path_buf(),
             ignore_sensitive_path: app_path.join(CONFIG_IGNORES),
             sensitive_commands_path: app_path.join(CONFIG_SENSITIVE_PATTERNS),
+            cli_flags_path: app_path.join(CONFIG_CLI_FLAGS),
+            url_params_path: app_path.join(CONFIG_URL_PARAMS),
+            fingerprint_salt_path: app_path.join(CONFIG_FINGERPRINT_SALT),
+            baseline_path: app_path.join(CONFIG_BASELINE),
+            pattern_packs_path: app_path.join(CONFIG_PATTERN_PACKS),
+            packs_config_path: app_path.join(CONFIG_PACKS),
+            settings_path: app_path.join(CONFIG_SETTINGS),
+            profiles_path: app_path.join(CONFIG_PROFILES),
+            profile: None,
+            profile_settings_path: None,
+            user_settings_path: xdg_dir("XDG_CONFIG_HOME", ".config")
+                .map(|dir| Self::get_base_app_folder(&dir).join(CONFIG_SETTINGS)),
+            system_settings_path: Path::new(SYSTEM_CONFIG_FOLDER).join(CONFIG_SETTINGS),
+            system_patterns_path: Path::new(SYSTEM_CONFIG_FOLDER).join(CONFIG_SENSITIVE_PATTERNS),
+            project_patterns_path: Self::find_project_patterns(),
             app_path,
         }
     }
 
+    /// Store the backups and stash in the given folder
+    #[must_use]
+    pub fn with_state_path(mut self, path: &Path) -> Self {
+        self.state_path = path.to_path_buf();
+        self
+    }
+
+    /// Move the shellclear files of the config and state folders used before
+    /// the XDG layout, `~/shellclear` and `~/.shellclear`. other files are left
+    /// in place, existing files are never overwritten and the old folder is
+    /// removed once it's empty
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` when a folder could not be read or a file could not
+    /// be moved
+    pub fn migrate_legacy_folders(&self, home: &Path) -> Result<Vec<PathBuf>> {
+        let mut moved = migrate_folder(
+            &home.join(LEGACY_CONFIG_FOLDER),
+            &self.app_path,
+            LEGACY_CONFIG_ENTRIES,
+        )?;
+        moved.extend(migrate_folder(
+            &home.join(LEGACY_STATE_FOLDER),
+            &self.state_path,
+            LEGACY_STATE_ENTRIES,
+        )?);
+        Ok(moved)
+    }
+
+    /// Select a profile of the config folder. the profile has its own settings,
+    /// ignores and pack selection
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` when the profile doesn't exist
+    pub fn with_profile(mut self, name: &str) -> Result<Self> {
+        let path = self.profile_path(name)?;
+        if !path.is_dir() {
+            return Err(anyhow!(
+                "profile {} not found. create it with `shellclear config profiles create {}`",
+                name,
+                name
+            ));
+        }
+        self.ignore_sensitive_path = path.join(CONFIG_IGNORES);
+        self.packs_config_path = path.join(CONFIG_PACKS);
+        self.profile_settings_path = Some(path.join(CONFIG_SETTINGS));
+        self.profile = Some(name.to_string());
+        Ok(self)
+    }
+
+    /// Directory of the profile
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` when the name is not a valid profile name
+    pub fn profile_path(&self, name: &str) -> Result<PathBuf> {
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(anyhow!(
+                "invalid profile name {name}. use letters, digits, `-` and `_`"
+            ));
+        }
+        Ok(self.profiles_path.join(name))
+    }
+
+    /// Names of the profiles, sorted
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` when the profiles directory could not be read
+    pub fn list_profiles(&self) -> Result<Vec<String>> {
+        if !self.profiles_path.exists() {
+            return Ok(vec![]);
+        }
+        let mut names = fs::read_dir(&self.profiles_path)?
+            .filter_map(std::result::Result::ok)
+            .filter(|e| e.path().is_dir())
+            .filter_map(|e| e.file_name().to_str().map(ToString::to_string))
+            .collect::<Vec<_>>();
+        names.sort();
+        Ok(names)
+    }
+
+    /// Create a profile with a settings template
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` when the profile exists or has an error to write a file
+    pub fn create_profile(&self, name: &str) -> Result<PathBuf> {
+        let path = self.profile_path(name)?;
+        if path.exists() {
+            return Err(anyhow!("profile {name} already exists"));
+        }
+        fs::create_dir_all(&path)?;
+        fs::write(path.join(CONFIG_SETTINGS), PROFILE_SETTINGS_TEMPLATE)?;
+        Ok(path)
+    }
+
+    /// Create a profile with the files of another profile
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` when the source profile doesn't exist, the target
+    /// profile exists or has an error to copy a file
+    pub fn copy_profile(&self, from: &str, to: &str) -> Result<PathBuf> {
+        let from_path = self.profile_path(from)?;
+        let to_path = self.profile_path(to)?;
+        if !from_path.is_dir() {
+            return Err(anyhow!("profile {from} not found"));
+        }
+        if to_path.exists() {
+            return Err(anyhow!("profile {to} already exists"));
+        }
+        fs::create_dir_all(&to_path)?;
+        for entry in fs::read_dir(&from_path)? {
+            let entry = entry?;
+            if entry.path().is_file() {
+                fs::copy(entry.path(), to_path.join(entry.file_name()))?;
+            }
+        }
+        Ok(to_path)
+    }
+
+    /// Search the project patterns file from the current directory up
+    fn find_project_patterns() -> Option<PathBuf> {
+        let current_dir = std::env::current_dir().ok()?;
+        current_dir
+            .ancestors()
+            .map(|dir| {
+                dir.join(PROJECT_CONFIG_FOLDER)
+                    .join(CONFIG_SENSITIVE_PATTERNS)
+            })
+            .find(|path| path.exists())
+    }
+
     /// Returns the root shellclear config folder
     fn get_base_app_folder(path: &Path) -> PathBuf {
         path.join(ROOT_APP_FOLDER)
@@ -123,53 +391,528 @@ impl Config {
     ///
     /// Will return `Err` home directory not found or yaml is invalid
     pub fn load_patterns_from_default_path(&self) -> Result<Vec<Detection>> {
-        let f = std::fs::File::open(&self.sensitive_commands_path)?;
-        let custom_patterns = serde_yaml::from_reader(f)?;
+        Self::load_patterns(&self.sensitive_commands_path)
+    }
+
+    /// Add patterns to the custom sensitive patterns file. the file is edited
+    /// in place, so its comments and formatting stay
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` when the file is invalid or could not be written
+    pub fn add_patterns(&self, added: &[Detection]) -> Result<()> {
+        let mut patterns = self.load_existing_patterns()?;
+        patterns.extend_from_slice(added);
+        edit_yaml_sequence(&self.sensitive_commands_path, &patterns, &[], added.len())
+    }
+
+    /// Remove the patterns with the id from the custom sensitive patterns
+    /// file, returns the number of removed patterns
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` when the file is invalid or could not be written
+    pub fn remove_pattern(&self, id: &str) -> Result<usize> {
+        let patterns = self.load_existing_patterns()?;
+        let removed = indexes(&patterns, |p| p.id == id);
+        if !removed.is_empty() {
+            let kept = patterns
+                .into_iter()
+                .filter(|p| p.id != id)
+                .collect::<Vec<_>>();
+            edit_yaml_sequence(&self.sensitive_commands_path, &kept, &removed, 0)?;
+        }
+        Ok(removed.len())
+    }
+
+    fn load_existing_patterns(&self) -> Result<Vec<Detection>> {
+        if self.sensitive_commands_path.exists() {
+            self.load_patterns_from_default_path()
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    /// Load a sensitive patterns file
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` file not found or yaml is invalid
+    pub fn load_patterns(path: &Path) -> Result<Vec<Detection>> {
+        let f = std::fs::File::open(path)?;
+        let custom_patterns: Vec<Detection> = serde_yaml::from_reader(f)?;
         log::debug!(
-            "found {:?} ignore ids. loaded from path: {}",
-            custom_patterns,
-            &self.sensitive_commands_path.display()
+            "found {} patterns. loaded from path: {}",
+            custom_patterns.len(),
+            path.display()
         );
         Ok(custom_patterns)
     }
 
-    /// Load sensitive ignores file
+    /// Load the pack directories and the disabled packs, the default config
+    /// when the file doesn't exist
     ///
     /// # Errors
     ///
     /// Will return `Err` yaml is invalid
-    pub fn get_ignore_patterns(&self) -> Result<Vec<String>> {
+    pub fn get_packs_config(&self) -> Result<PacksConfig> {
+        if !self.packs_config_path.exists() {
+            return Ok(PacksConfig::default());
+        }
+        let f = std::fs::File::open(&self.packs_config_path)?;
+        Ok(serde_yaml::from_reader(f)?)
+    }
+
+    /// Write the pack directories and the disabled packs
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` when has an error to write a file
+    pub fn save_packs_config(&self, packs_config: &PacksConfig) -> Result<()> {
+        if let Some(parent) = self.packs_config_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        Ok(fs::write(
+            &self.packs_config_path,
+            serde_yaml::to_string(packs_config)?,
+        )?)
+    }
+
+    /// Load custom tools whose arguments carry secrets
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` file not found or yaml is invalid
+    pub fn load_cli_flags_from_default_path(&self) -> Result<Vec<FlagRule>> {
+        let f = std::fs::File::open(&self.cli_flags_path)?;
+        let rules: Vec<FlagRule> = serde_yaml::from_reader(f)?;
+        log::debug!(
+            "found {} cli flag rules. loaded from path: {}",
+            rules.len(),
+            &self.cli_flags_path.display()
+        );
+        Ok(rules)
+    }
+
+    /// Load custom URL query parameter names that carry secrets
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` file not found or yaml is invalid
+    pub fn load_url_params_from_default_path(&self) -> Result<Vec<String>> {
+        let f = std::fs::File::open(&self.url_params_path)?;
+        let params = serde_yaml::from_reader(f)?;
+        log::debug!(
+            "found {:?} url query params. loaded from path: {}",
+            params,
+            &self.url_params_path.display()
+        );
+        Ok(params)
+    }
+
+    /// Load sensitive ignores file. an entry is a pattern id or an ignore rule
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` yaml is invalid
+    pub fn get_ignore_patterns(&self) -> Result<Vec<IgnoreRule>> {
         let f = std::fs::File::open(&self.ignore_sensitive_path)?;
-        let ignore_ids = serde_yaml::from_reader(f)?;
+        let entries: Vec<IgnoreEntry> = serde_yaml::from_reader(f)?;
+        let rules = entries
+            .into_iter()
+            .map(|entry| match entry {
+                IgnoreEntry::PatternId(id) => IgnoreRule::pattern(&id),
+                IgnoreEntry::Rule(rule) => rule,
+            })
+            .collect::<Vec<_>>();
         log::debug!(
-            "found {:?} ignore ids. loaded from path: {}",
-            ignore_ids,
+            "found {:?} ignore rules. loaded from path: {}",
+            rules,
             &self.ignore_sensitive_path.display()
         );
-        Ok(ignore_ids)
+        Ok(rules)
     }
 
-    /// Write a ignore patterns
+    /// Add ignore rules to the ignores file. the file is edited in place, so
+    /// its comments and formatting stay
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` when the file is invalid or could not be written
+    pub fn add_ignore_rules(&self, added: &[IgnoreRule]) -> Result<()> {
+        let mut rules = self.load_existing_ignore_rules()?;
+        rules.extend_from_slice(added);
+        let entries = ignore_entries(&rules);
+        edit_yaml_sequence(&self.ignore_sensitive_path, &entries, &[], added.len())
+    }
+
+    /// Remove the matching rules from the ignores file, returns the number of
+    /// removed rules
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` when the file is invalid or could not be written
+    pub fn remove_ignore_rules(&self, matches: impl Fn(&IgnoreRule) -> bool) -> Result<usize> {
+        let rules = self.load_existing_ignore_rules()?;
+        let removed = indexes(&rules, &matches);
+        if !removed.is_empty() {
+            let kept = rules
+                .into_iter()
+                .filter(|r| !matches(r))
+                .collect::<Vec<_>>();
+            let entries = ignore_entries(&kept);
+            edit_yaml_sequence(&self.ignore_sensitive_path, &entries, &removed, 0)?;
+        }
+        Ok(removed.len())
+    }
+
+    fn load_existing_ignore_rules(&self) -> Result<Vec<IgnoreRule>> {
+        if self.ignore_sensitive_path.exists() {
+            self.get_ignore_patterns()
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    /// Write a ignore rules, rules with only a pattern id are written as the id
     ///
     /// # Errors
     ///
     /// Will return `Err` when has an error to write a file
-    pub fn save_ignores_patterns(&self, ignores: &[String]) -> Result<()> {
+    pub fn save_ignores_patterns(&self, ignores: &[IgnoreRule]) -> Result<()> {
+        let entries = ignore_entries(ignores);
+        if let Some(parent) = self.ignore_sensitive_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
         Ok(fs::write(
             &self.ignore_sensitive_path,
-            serde_yaml::to_string(&ignores)?,
+            serde_yaml::to_string(&entries)?,
         )?)
     }
+
+    /// Load the fingerprints of the accepted findings from the given baseline
+    /// file, or from the default baseline in the config folder. a baseline
+    /// with only a list of fingerprints is read without a salt
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` file not found or yaml is invalid
+    pub fn get_baseline(&self, path: Option<&str>) -> Result<Baseline> {
+        let path = path.map_or_else(|| self.baseline_path.clone(), PathBuf::from);
+        let f = std::fs::File::open(&path)?;
+        let baseline = match serde_yaml::from_reader(f)? {
+            BaselineFile::Salted(baseline) => baseline,
+            BaselineFile::Fingerprints(fingerprints) => Baseline {
+                salt: None,
+                fingerprints,
+            },
+        };
+        log::debug!(
+            "found {} baseline fingerprints. loaded from path: {}",
+            baseline.fingerprints.len(),
+            path.display()
+        );
+        Ok(baseline)
+    }
+
+    /// Write the fingerprints of the current findings and their salt to the
+    /// given baseline file, or to the default baseline in the config folder
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` when has an error to write a file
+    pub fn save_baseline(&self, path: Option<&str>, baseline: &Baseline) -> Result<PathBuf> {
+        let path = match path {
+            Some(path) => PathBuf::from(path),
+            None => {
+                fs::create_dir_all(&self.app_path)?;
+                self.baseline_path.clone()
+            }
+        };
+        let mut baseline = baseline.clone();
+        baseline.fingerprints.sort();
+        baseline.fingerprints.dedup();
+        fs::write(&path, serde_yaml::to_string(&baseline)?)?;
+        Ok(path)
+    }
+
+    /// Load the salt of the secret fingerprints, `None` when it was never
+    /// created. scans only read the salt, they don't write to the config
+    /// folder
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` when the salt file could not be read
+    pub fn get_fingerprint_salt(&self) -> Result<Option<String>> {
+        if !self.fingerprint_salt_path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(
+            fs::read_to_string(&self.fingerprint_salt_path)?
+                .trim()
+                .to_string(),
+        ))
+    }
+
+    /// Load the salt of the secret fingerprints, a new random salt is created
+    /// on the first use. used when the fingerprints are shown, e.g. to write
+    /// ignore rules, so they are stable between runs
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` when the salt file could not be read or created
+    pub fn get_or_create_fingerprint_salt(&self) -> Result<String> {
+        if let Some(salt) = self.get_fingerprint_salt()? {
+            return Ok(salt);
+        }
+
+        let salt = fingerprint::new_salt()?;
+
+        fs::create_dir_all(&self.app_path)?;
+        fs::write(&self.fingerprint_salt_path, &salt)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(
+                &self.fingerprint_salt_path,
+                fs::Permissions::from_mode(0o600),
+            )?;
+        }
+        log::debug!(
+            "created fingerprint salt in path: {}",
+            self.fingerprint_salt_path.display()
+        );
+        Ok(salt)
+    }
+}
+
+/// The entries of the ignores file, rules with only a pattern id are written
+/// as the id
+fn ignore_entries(rules: &[IgnoreRule]) -> Vec<IgnoreEntry> {
+    rules
+        .iter()
+        .map(|rule| match &rule.pattern_id {
+            Some(id)
+                if rule.is_pattern_only() && rule.reason.is_none() && rule.expires.is_none() =>
+            {
+                IgnoreEntry::PatternId(id.clone())
+            }
+            _ => IgnoreEntry::Rule(rule.clone()),
+        })
+        .collect()
+}
+
+fn indexes<T>(items: &[T], matches: impl Fn(&T) -> bool) -> Vec<usize> {
+    items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| matches(item))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Write a YAML sequence file in place: the items at the `removed` indexes
+/// of the file are cut and the last `added` items are appended, so the
+/// comments and formatting of the other items stay. the file is written again
+/// from `items` when it is not a plain block sequence
+fn edit_yaml_sequence<T: serde::Serialize>(
+    path: &Path,
+    items: &[T],
+    removed: &[usize],
+    added: usize,
+) -> Result<()> {
+    let content = if path.exists() {
+        fs::read_to_string(path)?
+    } else {
+        String::new()
+    };
+    let edited = edit_yaml_items(&content, removed, &items[items.len() - added..])?
+        .filter(|edited| yaml_sequence_len(edited) == Some(items.len()));
+    let content = match edited {
+        Some(edited) => edited,
+        None => {
+            log::debug!(
+                "writing {} again, it is not a block sequence",
+                path.display()
+            );
+            serde_yaml::to_string(items)?
+        }
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(fs::write(path, content)?)
+}
+
+fn yaml_sequence_len(content: &str) -> Option<usize> {
+    serde_yaml::from_str::<Vec<serde_yaml::Value>>(content)
+        .ok()
+        .map(|items| items.len())
+}
+
+fn is_yaml_comment_or_blank(line: &str) -> bool {
+    let line = line.trim();
+    line.is_empty() || line.starts_with('#')
+}
+
+/// Cut and append items of a block sequence, `None` when the content is not
+/// a block sequence. an item owns the comment lines right above it
+fn edit_yaml_items<T: serde::Serialize>(
+    content: &str,
+    removed: &[usize],
+    added: &[T],
+) -> Result<Option<String>> {
+    let lines = content.split_inclusive('\n').collect::<Vec<_>>();
+    let is_item = |line: &str| {
+        let item = line.trim_start();
+        (item.starts_with("- ") || item.trim_end() == "-") && !item.starts_with("---")
+    };
+    let indent = lines
+        .iter()
+        .find(|line| is_item(line))
+        .map_or(0, |line| line.len() - line.trim_start().len());
+
+    // an empty sequence is `[]`, any other content at the item indent makes it
+    // something else than a block sequence
+    let mut starts = vec![];
+    let mut empty_sequence = vec![];
+    for (index, line) in lines.iter().enumerate() {
+        let line_indent = line.len() - line.trim_start().len();
+        if is_yaml_comment_or_blank(line) || line_indent > indent {
+            continue;
+        }
+        if line_indent == indent && is_item(line) {
+            starts.push(index);
+        } else if starts.is_empty() && matches!(line.trim(), "---" | "[]" | "--- []") {
+            empty_sequence.push(index);
+        } else {
+            return Ok(None);
+        }
+    }
+    let count = if content.trim().is_empty() {
+        0
+    } else {
+        match yaml_sequence_len(content) {
+            Some(count) => count,
+            None => return Ok(None),
+        }
+    };
+    if count != starts.len() || removed.iter().any(|&index| index >= count) {
+        return Ok(None);
+    }
+
+    // item ranges of lines, with the comments right above the item and the
+    // blank lines up to the next item
+    let mut ranges = vec![];
+    for (i, &start) in starts.iter().enumerate() {
+        let floor = if i == 0 { 0 } else { starts[i - 1] + 1 };
+        let mut first = start;
+        while first > floor && lines[first - 1].trim().starts_with('#') {
+            first -= 1;
+        }
+        ranges.push(first);
+    }
+    // the last item ends at its last line, the comments after it stay at the
+    // end of the file
+    let items_end = if starts.is_empty() {
+        lines.len()
+    } else {
+        lines
+            .iter()
+            .rposition(|line| !is_yaml_comment_or_blank(line))
+            .map_or(lines.len(), |index| index + 1)
+    };
+    let mut cut = vec![false; lines.len()];
+    for &index in removed {
+        let end = ranges.get(index + 1).copied().unwrap_or(items_end);
+        cut[ranges[index]..end].fill(true);
+    }
+    if count == 0 && !added.is_empty() {
+        for index in empty_sequence {
+            cut[index] = true;
+        }
+    }
+
+    let kept = |range: std::ops::Range<usize>| {
+        range
+            .filter(|&index| !cut[index])
+            .map(|index| lines[index])
+            .collect::<String>()
+    };
+    let mut edited = kept(0..items_end);
+    if !edited.is_empty() && !edited.ends_with('\n') {
+        edited.push('\n');
+    }
+    if !added.is_empty() {
+        let yaml = serde_yaml::to_string(added)?;
+        for line in yaml.strip_prefix("---\n").unwrap_or(&yaml).lines() {
+            edited.push_str(&format!("{}{line}\n", " ".repeat(indent)));
+        }
+    } else if count == removed.len() {
+        // a comment only file is not a valid patterns file
+        edited.push_str("[]\n");
+    }
+    edited.push_str(&kept(items_end..lines.len()));
+    Ok(Some(edited))
+}
+
+/// A baseline file, or a list of fingerprints written before the baseline had
+/// a salt
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BaselineFile {
+    Salted(Baseline),
+    Fingerprints(Vec<String>),
+}
+
+/// Move the given entries of the legacy folder that don't exist in the new
+/// folder
+fn migrate_folder(from: &Path, to: &Path, names: &[&str]) -> Result<Vec<PathBuf>> {
+    if !from.is_dir() || from == to {
+        return Ok(vec![]);
+    }
+    let mut moved = vec![];
+    for name in names {
+        let source = from.join(name);
+        if fs::symlink_metadata(&source).is_err() {
+            continue;
+        }
+        let target = to.join(name);
+        if target.exists() {
+            log::warn!(
+                "not migrating {}, {} already exists",
+                source.display(),
+                target.display()
+            );
+            continue;
+        }
+        fs::create_dir_all(to)?;
+        fs::rename(&source, &target)?;
+        log::info!("migrated {} to {}", source.display(), target.display());
+        moved.push(target);
+    }
+    if fs::read_dir(from)?.next().is_none() {
+        fs::remove_dir(from)?;
+        log::info!("removed empty legacy folder {}", from.display());
+    }
+    Ok(moved)
 }
 
 #[cfg(test)]
 mod test_config {
-    use std::{fs, path::PathBuf};
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
 
     use insta::assert_debug_snapshot;
+    use regex::Regex;
     use tempdir::TempDir;
 
-    use crate::{config::IGNORES_SENSITIVE_PATTERN_TEMPLATE, data::ROOT_APP_FOLDER};
+    use crate::{
+        config::IGNORES_SENSITIVE_PATTERN_TEMPLATE,
+        data::{Baseline, Detection, IgnoreRule, ROOT_APP_FOLDER},
+    };
 
     use super::{Config, CONFIG_IGNORES, CONFIG_SENSITIVE_PATTERNS, SENSITIVE_PATTERN_TEMPLATE};
 
@@ -185,18 +928,18 @@ mod test_config {
         let path = PathBuf::from(cfg_dir);
         let config = Config::from(path.as_os_str().to_str());
         assert_debug_snapshot!(
-            config.app_path == PathBuf::from(format!("{}/{}", cfg_dir, ROOT_APP_FOLDER))
+            config.app_path == Path::new(&format!("{}/{}", cfg_dir, ROOT_APP_FOLDER))
         );
         assert_debug_snapshot!(
             config.ignore_sensitive_path
-                == PathBuf::from(format!(
+                == Path::new(&format!(
                     "{}/{}/{}",
                     cfg_dir, ROOT_APP_FOLDER, CONFIG_IGNORES
                 ))
         );
         assert_debug_snapshot!(
             config.sensitive_commands_path
-                == PathBuf::from(format!(
+                == Path::new(&format!(
                     "{}/{}/{}",
                     cfg_dir, ROOT_APP_FOLDER, CONFIG_SENSITIVE_PATTERNS
                 ))
@@ -240,7 +983,7 @@ mod test_config {
             .sensitive_commands_path
             .display()
             .to_string()
-            .replace(&temp_dir.path().to_str().unwrap(), "")
+            .replace(temp_dir.path().to_str().unwrap(), "")
             .replace('\\', "/"));
         temp_dir.close().unwrap();
     }
@@ -291,10 +1034,178 @@ mod test_config {
         let temp_dir = TempDir::new("config-app").unwrap();
         let config = new_config(&temp_dir);
         config.init().unwrap();
-        assert_debug_snapshot!(
-            config.save_ignores_patterns(&["patter-1".to_string(), "patter-2".to_string()])
-        );
+        assert_debug_snapshot!(config.save_ignores_patterns(&[
+            IgnoreRule::pattern("patter-1"),
+            IgnoreRule::pattern("patter-2")
+        ]));
         assert_debug_snapshot!(config.get_ignore_patterns());
         temp_dir.close().unwrap();
     }
+
+    #[test]
+    fn can_load_ignore_rules() {
+        let temp_dir = TempDir::new("config-app").unwrap();
+        let config = new_config(&temp_dir);
+        config.init().unwrap();
+        fs::write(
+            &config.ignore_sensitive_path,
+            r###"
+- github_env_token
+- pattern_id: stripe_key
+  secret_fingerprint: 27080dbae497784e
+  reason: known fake test token
+  expires: 2022-01-01
+- command_regex: ^cd /tmp/sandbox
+  shell: zsh
+"###,
+        )
+        .unwrap();
+        let rules = config.get_ignore_patterns().unwrap();
+        assert_debug_snapshot!(rules);
+
+        config.save_ignores_patterns(&rules).unwrap();
+        assert_debug_snapshot!(fs::read_to_string(&config.ignore_sensitive_path).unwrap());
+        temp_dir.close().unwrap();
+    }
+
+    #[test]
+    fn can_edit_patterns_in_place() {
+        let temp_dir = TempDir::new("config-app").unwrap();
+        let config = new_config(&temp_dir);
+        fs::create_dir_all(&config.app_path).unwrap();
+        fs::write(
+            &config.sensitive_commands_path,
+            r###"# team patterns
+
+# the internal deploy token
+- id: deploy_token
+  name: Deploy Token
+  test: DEPLOY_TOKEN=(\w+)  # the token value
+  secret_group: 1
+
+- id: old_token
+  name: Old Token
+  test: OLD_TOKEN=(\w+)
+  secret_group: 1
+# keep this note at the end
+"###,
+        )
+        .unwrap();
+
+        let pattern = Detection::new(
+            "new_token",
+            "New Token",
+            Regex::new("NEW=(\\w+)").unwrap(),
+            1,
+        );
+        config.add_patterns(&[pattern]).unwrap();
+        assert_debug_snapshot!(config.remove_pattern("old_token").unwrap());
+        assert_debug_snapshot!(fs::read_to_string(&config.sensitive_commands_path).unwrap());
+
+        assert_debug_snapshot!((
+            config.remove_pattern("deploy_token").unwrap(),
+            config.remove_pattern("new_token").unwrap(),
+        ));
+        assert_debug_snapshot!(fs::read_to_string(&config.sensitive_commands_path).unwrap());
+        assert_debug_snapshot!(config.load_patterns_from_default_path().unwrap().len());
+        temp_dir.close().unwrap();
+    }
+
+    #[test]
+    fn can_get_or_create_fingerprint_salt() {
+        let temp_dir = TempDir::new("config-app").unwrap();
+        let config = new_config(&temp_dir);
+        assert_debug_snapshot!(config.get_fingerprint_salt().unwrap());
+        let salt = config.get_or_create_fingerprint_salt().unwrap();
+        assert_debug_snapshot!(salt.len());
+        assert_debug_snapshot!(config.get_or_create_fingerprint_salt().unwrap() == salt);
+        temp_dir.close().unwrap();
+    }
+
+    #[test]
+    fn can_save_baseline() {
+        let temp_dir = TempDir::new("config-app").unwrap();
+        let config = new_config(&temp_dir);
+        let baseline = Baseline {
+            salt: Some("salt".to_string()),
+            fingerprints: vec!["b".to_string(), "a".to_string(), "b".to_string()],
+        };
+        assert_debug_snapshot!(config.save_baseline(None, &baseline).is_ok());
+        assert_debug_snapshot!(config.get_baseline(None));
+
+        // a baseline written before the salt was stored
+        let path = temp_dir.path().join("baseline.yaml");
+        fs::write(&path, "- b\n").unwrap();
+        assert_debug_snapshot!(config.get_baseline(path.to_str()));
+        temp_dir.close().unwrap();
+    }
+
+    #[test]
+    fn can_migrate_legacy_folders() {
+        let temp_dir = TempDir::new("config-app").unwrap();
+        let home = temp_dir.path().join("home");
+        fs::create_dir_all(home.join("shellclear")).unwrap();
+        fs::create_dir_all(home.join(".shellclear/stash")).unwrap();
+        fs::write(home.join("shellclear/ignores.yaml"), "- legacy\n").unwrap();
+        fs::write(home.join("shellclear/baseline.yaml"), "[]\n").unwrap();
+        fs::write(home.join(".shellclear/stash/.bash_history"), "ls\n").unwrap();
+        // files that are not shellclear's, and a project config in the home
+        fs::write(home.join("shellclear/notes.txt"), "mine\n").unwrap();
+        fs::write(home.join(".shellclear/sensitive-patterns.yaml"), "[]\n").unwrap();
+
+        let config = Config::with_paths(
+            &temp_dir.path().join("config/shellclear"),
+            &temp_dir.path().join("state/shellclear"),
+        );
+        // an existing file is kept and the legacy file stays in place
+        fs::create_dir_all(&config.app_path).unwrap();
+        fs::write(&config.ignore_sensitive_path, "- new\n").unwrap();
+
+        let mut moved = config
+            .migrate_legacy_folders(&home)
+            .unwrap()
+            .iter()
+            .map(|p| {
+                p.strip_prefix(temp_dir.path())
+                    .unwrap()
+                    .display()
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        moved.sort();
+        assert_debug_snapshot!(moved);
+        assert_debug_snapshot!((
+            fs::read_to_string(&config.ignore_sensitive_path).unwrap(),
+            home.join("shellclear/ignores.yaml").exists(),
+            home.join("shellclear/notes.txt").exists(),
+            home.join(".shellclear/sensitive-patterns.yaml").exists(),
+            home.join(".shellclear/stash").exists(),
+            config.migrate_legacy_folders(&home).unwrap().len(),
+        ));
+        temp_dir.close().unwrap();
+    }
+
+    #[test]
+    fn can_create_and_copy_profiles() {
+        let temp_dir = TempDir::new("config-app").unwrap();
+        let config = new_config(&temp_dir);
+        config.create_profile("work").unwrap();
+        let work = config.clone().with_profile("work").unwrap();
+        work.save_ignores_patterns(&[IgnoreRule::pattern("github_env_token")])
+            .unwrap();
+        config.copy_profile("work", "personal").unwrap();
+
+        assert_debug_snapshot!(config.list_profiles());
+        assert_debug_snapshot!(config
+            .clone()
+            .with_profile("personal")
+            .unwrap()
+            .get_ignore_patterns());
+        assert_debug_snapshot!((
+            config.create_profile("work").is_err(),
+            config.create_profile("../escape").is_err(),
+            config.clone().with_profile("missing").is_err(),
+        ));
+        temp_dir.close().unwrap();
+    }
 }
@@ -0,0 +1,177 @@
+use std::{
+    collections::HashSet,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{data::Command, settings::CorrelationSettings};
+
+/// incident ids are unique across the history files of a run, the findings of
+/// several files are shown together
+static NEXT_INCIDENT: AtomicUsize = AtomicUsize::new(0);
+
+fn vendors(command: &Command) -> HashSet<&str> {
+    command
+        .detections
+        .iter()
+        .filter_map(|d| d.vendor.as_deref())
+        .collect()
+}
+
+fn ids(command: &Command) -> HashSet<&str> {
+    command.detections.iter().map(|d| d.id.as_str()).collect()
+}
+
+/// Group findings of the same vendor in adjacent commands, e.g. an AWS key id
+/// exported on one line and the secret on the next, to a single credential
+/// incident
+pub fn correlate(commands: &mut [Command], settings: &CorrelationSettings) {
+    let timestamps = commands.iter().map(Command::timestamp).collect::<Vec<_>>();
+
+    for i in 0..commands.len() {
+        let current = vendors(&commands[i]);
+        if current.is_empty() {
+            continue;
+        }
+
+        for j in (0..i).rev() {
+            let close_in_time = match (timestamps[i], timestamps[j]) {
+                (Some(a), Some(b)) => (a - b).abs() <= settings.max_seconds,
+                _ => false,
+            };
+            if i - j > settings.max_distance && !close_in_time {
+                break;
+            }
+            // the same findings again are a repeated command, not a pair
+            if vendors(&commands[j]).is_disjoint(&current) || ids(&commands[j]) == ids(&commands[i])
+            {
+                continue;
+            }
+            let incident = commands[j]
+                .incident
+                .unwrap_or_else(|| NEXT_INCIDENT.fetch_add(1, Ordering::Relaxed));
+            commands[j].incident = Some(incident);
+            commands[i].incident = Some(incident);
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_correlator {
+    use insta::assert_debug_snapshot;
+    use regex::Regex;
+
+    use super::*;
+    use crate::{
+        data::{Detection, FormatCheck},
+        shell::Shell,
+    };
+
+    fn command(data: &str, ids: &[(&str, &str)]) -> Command {
+        Command {
+            shell_type: Shell::Zshrc,
+            detections: ids
+                .iter()
+                .map(|(id, vendor)| {
+                    let mut detection = Detection::new(id, id, Regex::new(id).unwrap(), 0);
+                    detection.vendor = Some((*vendor).to_string()).filter(|v| !v.is_empty());
+                    detection
+                })
+                .collect(),
+            command: data.split_once(';').unwrap().1.to_string(),
+            data: data.to_string(),
+            secrets: vec![],
+            spans: vec![],
+            jwt_claims: vec![],
+            format_check: FormatCheck::Unchecked,
+            incident: None,
+            fingerprints: vec![],
+        }
+    }
+
+    #[test]
+    fn can_correlate_findings() {
+        let mut commands = vec![
+            command(
+                ": 1655110559:0;export AWS_ACCESS_KEY_ID=x",
+                &[("aws_end_access_key", "aws")],
+            ),
+            command(
+                ": 1655110560:0;export AWS_SECRET_ACCESS_KEY=y",
+                &[("aws_env_secret_key", "aws")],
+            ),
+            command(
+                ": 1655110561:0;export GITHUB_TOKEN=z",
+                &[("github_env_token", "")],
+            ),
+            command(": 1655110562:0;ls", &[]),
+            command(": 1655110563:0;ls", &[]),
+            command(": 1655110564:0;ls", &[]),
+            command(": 1655119999:0;ls", &[]),
+            command(
+                ": 1655120000:0;export AWS_ACCESS_KEY_ID=x",
+                &[("aws_end_access_key", "aws")],
+            ),
+            command(
+                ": 1655120001:0;export AWS_ACCESS_KEY_ID=x",
+                &[("aws_end_access_key", "aws")],
+            ),
+        ];
+
+        correlate(&mut commands, &CorrelationSettings::default());
+        assert_debug_snapshot!(incidents(&commands));
+
+        // a shorter window doesn't correlate the pair
+        for command in &mut commands {
+            command.incident = None;
+        }
+        correlate(
+            &mut commands,
+            &CorrelationSettings {
+                max_distance: 0,
+                max_seconds: 0,
+            },
+        );
+        assert_debug_snapshot!(commands.iter().all(|c| c.incident.is_none()));
+    }
+
+    #[test]
+    fn can_keep_incidents_of_history_files_apart() {
+        let history = || {
+            vec![
+                command(
+                    ": 1655110559:0;export AWS_ACCESS_KEY_ID=x",
+                    &[("aws_end_access_key", "aws")],
+                ),
+                command(
+                    ": 1655110560:0;export AWS_SECRET_ACCESS_KEY=y",
+                    &[("aws_env_secret_key", "aws")],
+                ),
+            ]
+        };
+        let mut first = history();
+        let mut second = history();
+        correlate(&mut first, &CorrelationSettings::default());
+        correlate(&mut second, &CorrelationSettings::default());
+
+        assert_debug_snapshot!((
+            first[0].incident == first[1].incident,
+            second[0].incident == second[1].incident,
+            first[0].incident == second[0].incident,
+        ));
+    }
+
+    /// the commands with the index of the first command of their incident, the
+    /// incident ids depend on the other tests
+    fn incidents(commands: &[Command]) -> Vec<(String, Option<usize>)> {
+        commands
+            .iter()
+            .map(|c| {
+                let first = c.incident.and_then(|incident| {
+                    commands.iter().position(|o| o.incident == Some(incident))
+                });
+                (c.command.clone(), first)
+            })
+            .collect()
+    }
+}
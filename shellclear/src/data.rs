@@ -1,6 +1,10 @@
-use serde_derive::Deserialize;
+use std::ops::Range;
 
-use crate::shell::Shell;
+use chrono::NaiveDate;
+use serde_derive::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+use crate::shell::{FishHistory, Shell};
 
 pub const ROOT_APP_FOLDER: &str = env!("CARGO_PKG_NAME");
 
@@ -10,14 +14,327 @@ pub struct CmdExit {
     pub message: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Detection {
     #[serde(with = "serde_regex")]
     pub test: regex::Regex,
     pub name: String,
     pub secret_group: u8,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub id: String,
+    /// name of a built-in offline validator of the secret format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validator: Option<String>,
+    /// findings of the same vendor in adjacent commands are correlated to a
+    /// single credential incident, e.g. an AWS key id and secret
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vendor: Option<String>,
+    #[serde(default, skip_serializing_if = "Severity::is_default")]
+    pub severity: Severity,
+    /// kind of service, e.g. `cloud`, `vcs`, `chat`, `payment`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// how to revoke or rotate the secret
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+    /// the command must contain one of the keywords (case insensitive)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    /// minimum Shannon entropy of the secret, in bits per character
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_entropy: Option<f64>,
+    /// commands the pattern should match, with the expected secret
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<PatternExample>,
+    /// commands the pattern should not match
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub counter_examples: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PatternExample {
+    pub text: String,
+    pub secret: String,
+}
+
+impl Detection {
+    /// Create a detection without the optional fields
+    #[must_use]
+    pub fn new(id: &str, name: &str, test: regex::Regex, secret_group: u8) -> Self {
+        Self {
+            test,
+            name: name.to_string(),
+            secret_group,
+            id: id.to_string(),
+            validator: None,
+            vendor: None,
+            severity: Severity::default(),
+            category: None,
+            tags: vec![],
+            description: None,
+            remediation: None,
+            keywords: vec![],
+            min_entropy: None,
+            examples: vec![],
+            counter_examples: vec![],
+        }
+    }
+
+    /// Does the command contain one of the keywords, always true without
+    /// keywords
+    #[must_use]
+    pub fn has_keyword(&self, command: &str) -> bool {
+        if self.keywords.is_empty() {
+            return true;
+        }
+        let command = command.to_lowercase();
+        self.keywords
+            .iter()
+            .any(|k| command.contains(&k.to_lowercase()))
+    }
+
+    /// Is the secret random enough for the pattern minimum entropy
+    #[must_use]
+    pub fn has_min_entropy(&self, secret: &str) -> bool {
+        self.min_entropy
+            .is_none_or(|min| shannon_entropy(secret) >= min)
+    }
+
+    /// Is the tag one of the detection tags or its category
+    #[must_use]
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.category
+            .iter()
+            .chain(&self.tags)
+            .any(|t| t.eq_ignore_ascii_case(tag))
+    }
+}
+
+/// Shannon entropy of the string, in bits per character
+fn shannon_entropy(text: &str) -> f64 {
+    let mut counts = std::collections::HashMap::new();
+    for c in text.chars() {
+        *counts.entry(c).or_insert(0_usize) += 1;
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let len = text.chars().count() as f64;
+    counts
+        .values()
+        .map(|&count| {
+            #[allow(clippy::cast_precision_loss)]
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Impact of a leaked secret
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Deserialize,
+    Serialize,
+    Display,
+    EnumString,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Severity {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    #[must_use]
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Keep only the findings with the given minimum severity and tags
+#[derive(Debug, Clone, Default)]
+pub struct FindingFilter {
+    pub min_severity: Option<Severity>,
+    /// keep findings with at least one of the tags
+    pub tags: Vec<String>,
+    /// drop findings with any of the tags
+    pub exclude_tags: Vec<String>,
+}
+
+impl FindingFilter {
+    #[must_use]
+    pub fn accepts(&self, detection: &Detection) -> bool {
+        self.min_severity.is_none_or(|s| detection.severity >= s)
+            && (self.tags.is_empty() || self.tags.iter().any(|t| detection.has_tag(t)))
+            && !self.exclude_tags.iter().any(|t| detection.has_tag(t))
+    }
+}
+
+/// Result of the offline format validators of the command secrets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum FormatCheck {
+    /// none of the detections has a validator
+    Unchecked,
+    /// at least one secret has a valid format
+    Confirmed,
+    /// all the validated secrets have an invalid format
+    Mismatch,
+}
+
+/// Describe a tool whose arguments carry secrets, e.g. `mysql -pS3cret`
+#[derive(Debug, Deserialize, Clone)]
+pub struct FlagRule {
+    pub id: String,
+    pub name: String,
+    /// program name, compared with the basename of the executed program
+    pub tool: String,
+    /// leading positional words that must follow the tool, e.g. `login`
+    #[serde(default)]
+    pub subcommand: Vec<String>,
+    /// flags followed by a secret, attached (`--password=x`, `-px`) or as the
+    /// next argument
+    #[serde(default)]
+    pub flags: Vec<String>,
+    /// flags that only carry a secret when the value is attached
+    #[serde(default)]
+    pub attached_flags: Vec<String>,
+    /// flags followed by a value that is not a secret, e.g. `-C 10`. their
+    /// values, and the values of `flags`, are not counted as positionals
+    #[serde(default)]
+    pub value_flags: Vec<String>,
+    /// index of a positional argument that carries a secret
+    #[serde(default)]
+    pub positional: Option<usize>,
+    /// flag that must be present for `positional` to carry a secret
+    #[serde(default)]
+    pub positional_flag: Option<String>,
+    /// extract the secret from the argument value with the first capture group.
+    /// without `flags` and `positional`, every argument is matched
+    #[serde(default, with = "serde_regex")]
+    pub value: Option<regex::Regex>,
+    #[serde(default)]
+    pub severity: Severity,
+    #[serde(default)]
+    pub category: Option<String>,
+    /// how to revoke or rotate the secret
+    #[serde(default)]
+    pub remediation: Option<String>,
+}
+
+/// Fingerprints of the accepted findings. the baseline has its own salt, so it
+/// matches the findings of any machine
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct Baseline {
+    /// salt of the fingerprints. baselines without a salt were written with the
+    /// salt of the config folder
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub salt: Option<String>,
+    pub fingerprints: Vec<String>,
+}
+
+/// Ignore findings that match all the given fields
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct IgnoreRule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern_id: Option<String>,
+    /// fingerprint of the secret, as shown by `find --group-by secret`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_fingerprint: Option<String>,
+    #[serde(default, with = "serde_regex", skip_serializing_if = "Option::is_none")]
+    pub command_regex: Option<regex::Regex>,
+    /// shell name, e.g. `bash`, `zshrc`, `fish`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shell: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// the rule is not applied after this date (`YYYY-MM-DD`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<NaiveDate>,
+}
+
+impl IgnoreRule {
+    /// Ignore a whole pattern by id, the format of the old ignores file
+    #[must_use]
+    pub fn pattern(id: &str) -> Self {
+        Self {
+            pattern_id: Some(id.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// The rule only has a pattern id, so the pattern is disabled
+    #[must_use]
+    pub fn is_pattern_only(&self) -> bool {
+        self.pattern_id.is_some()
+            && self.secret_fingerprint.is_none()
+            && self.command_regex.is_none()
+            && self.shell.is_none()
+    }
+
+    /// The rule has none of the fields to match a finding
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pattern_id.is_none()
+            && self.secret_fingerprint.is_none()
+            && self.command_regex.is_none()
+            && self.shell.is_none()
+    }
+
+    #[must_use]
+    pub fn is_expired(&self, today: NaiveDate) -> bool {
+        self.expires.is_some_and(|expires| expires < today)
+    }
+
+    /// Does the rule match the finding at the given index of the command
+    #[must_use]
+    pub fn matches(&self, command: &Command, index: usize) -> bool {
+        let Some(detection) = command.detections.get(index) else {
+            return false;
+        };
+        if self.is_empty() {
+            return false;
+        }
+        self.pattern_id
+            .as_ref()
+            .is_none_or(|id| id == &detection.id)
+            && self
+                .secret_fingerprint
+                .as_ref()
+                .is_none_or(|f| command.fingerprints.get(index) == Some(f))
+            && self
+                .command_regex
+                .as_ref()
+                .is_none_or(|r| r.is_match(&command.command))
+            && self
+                .shell
+                .as_ref()
+                .is_none_or(|shell| command.shell_type.is_named(shell))
+    }
+}
+
+/// Claims decoded from a JSON Web Token, the signature is not verified
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JwtClaims {
+    pub alg: Option<String>,
+    pub iss: Option<String>,
+    pub sub: Option<String>,
+    /// expiration time as unix timestamp
+    pub exp: Option<i64>,
 }
 
 #[derive(Debug, Clone)]
@@ -27,4 +344,54 @@ pub struct Command {
     pub command: String,
     pub data: String,
     pub secrets: Vec<String>,
+    /// byte ranges in `command` of secrets that should be masked in place
+    pub spans: Vec<Range<usize>>,
+    /// decoded claims of the secrets that are JSON Web Tokens
+    pub jwt_claims: Vec<JwtClaims>,
+    pub format_check: FormatCheck,
+    /// id of the correlated credential incident, unique across the scanned
+    /// history files
+    pub incident: Option<usize>,
+    /// salted fingerprint of every secret, see `fingerprint::fingerprint`
+    pub fingerprints: Vec<String>,
+}
+
+/// Findings of the same secret across shells and history files
+#[derive(Debug, Clone)]
+pub struct SecretGroup {
+    pub fingerprint: String,
+    /// names of the detections that found the secret
+    pub names: Vec<String>,
+    /// number of findings of the secret
+    pub count: usize,
+    pub shells: Vec<Shell>,
+    /// where the secret was seen, e.g. history file paths, image layers or
+    /// repository commits
+    pub sources: Vec<String>,
+    /// first and last execution time as unix timestamp, when the shell
+    /// records it
+    pub first_seen: Option<i64>,
+    pub last_seen: Option<i64>,
+}
+
+impl Command {
+    /// Command execution time as unix timestamp, when the shell records it
+    #[must_use]
+    pub fn timestamp(&self) -> Option<i64> {
+        match self.shell_type {
+            Shell::Zshrc => self
+                .data
+                .strip_prefix(": ")?
+                .split(':')
+                .next()?
+                .parse()
+                .ok(),
+            Shell::Fish => serde_yaml::from_str::<FishHistory>(&self.data)
+                .ok()?
+                .when
+                .parse()
+                .ok(),
+            _ => None,
+        }
+    }
 }
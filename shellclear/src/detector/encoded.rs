@@ -0,0 +1,89 @@
+use std::ops::Range;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    static ref ENCODED_TOKEN: Regex = Regex::new(r"[A-Za-z0-9+/_-]{24,}={0,2}").unwrap();
+    static ref HEX_TOKEN: Regex = Regex::new(r"^(?:[0-9a-fA-F]{2}){16,}$").unwrap();
+}
+
+/// Long base64 or hex token in a command and its decoded content
+#[derive(Debug, Clone)]
+pub struct EncodedToken {
+    pub encoded: String,
+    pub decoded: String,
+    /// byte range of the encoded token in the command
+    pub span: Range<usize>,
+}
+
+fn decode_hex(token: &str) -> Option<Vec<u8>> {
+    (0..token.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&token[i..i + 2], 16).ok())
+        .collect()
+}
+
+fn decode_base64(token: &str) -> Option<Vec<u8>> {
+    let token = token.trim_end_matches('=');
+    base64::decode_config(token, base64::STANDARD_NO_PAD)
+        .or_else(|_| base64::decode_config(token, base64::URL_SAFE_NO_PAD))
+        .ok()
+}
+
+/// decode the token and keep only text content
+fn decode(token: &str) -> Option<String> {
+    let bytes = if HEX_TOKEN.is_match(token) {
+        decode_hex(token)?
+    } else {
+        decode_base64(token)?
+    };
+    let text = String::from_utf8(bytes).ok()?;
+    if text
+        .chars()
+        .all(|c| !c.is_control() || c.is_ascii_whitespace())
+    {
+        Some(text)
+    } else {
+        None
+    }
+}
+
+/// Find long base64/hex tokens in the command that decode to text
+#[must_use]
+pub fn decode_tokens(command: &str) -> Vec<EncodedToken> {
+    ENCODED_TOKEN
+        .find_iter(command)
+        .filter_map(|m| {
+            Some(EncodedToken {
+                encoded: m.as_str().to_string(),
+                decoded: decode(m.as_str())?,
+                span: m.range(),
+            })
+        })
+        .collect::<Vec<_>>()
+}
+
+#[cfg(test)]
+mod test_encoded {
+    use insta::assert_debug_snapshot;
+
+    use super::*;
+
+    #[test]
+    fn can_decode_tokens() {
+        let commands = [
+            "echo ZXhwb3J0IEdJVEhVQl9UT0tFTj1naHBfc2VjcmV0 | base64 -d > creds.sh",
+            "echo 6578706f7274204649445f4d453d746f6b656e | xxd -r -p",
+            "git checkout 3f786850e387550fdab836ed7e6dc881de23001b",
+            "ls /usr/local/lib/python3.10/site-packages",
+        ];
+
+        let results = commands
+            .iter()
+            .map(|command| decode_tokens(command))
+            .collect::<Vec<_>>();
+
+        assert_debug_snapshot!(results);
+    }
+}
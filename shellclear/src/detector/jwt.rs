@@ -0,0 +1,121 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::{
+    data::{Detection, JwtClaims, Severity},
+    detector::{Detector, Finding},
+};
+
+lazy_static! {
+    static ref JWT: Regex = Regex::new(
+        r"\beyJ[A-Za-z0-9_-]{5,}={0,2}\.eyJ[A-Za-z0-9_-]{5,}={0,2}\.[A-Za-z0-9_-]*={0,2}"
+    )
+    .unwrap();
+}
+
+/// Detect JSON Web Tokens, e.g. `Authorization: Bearer eyJ...`
+pub struct JwtDetector {
+    detection: Detection,
+}
+
+impl Default for JwtDetector {
+    fn default() -> Self {
+        Self {
+            detection: Detection {
+                severity: Severity::Medium,
+                category: Some("token".to_string()),
+                description: Some("Bearer token, valid until its expiry".to_string()),
+                remediation: Some(
+                    "Revoke the session or rotate the signing key if the token is live".to_string(),
+                ),
+                ..Detection::new("jwt", "JSON Web Token", JWT.clone(), 0)
+            },
+        }
+    }
+}
+
+/// decode a base64url JWT part into a JSON object
+fn decode_part(part: &str) -> Option<serde_json::Map<String, Value>> {
+    let bytes = base64::decode_config(part.trim_end_matches('='), base64::URL_SAFE_NO_PAD).ok()?;
+    match serde_json::from_slice(&bytes).ok()? {
+        Value::Object(map) => Some(map),
+        _ => None,
+    }
+}
+
+/// Decode the header and the payload of the given token without verifying
+/// the signature. returns `None` when the token is not a JWT.
+#[must_use]
+pub fn decode(token: &str) -> Option<JwtClaims> {
+    let mut parts = token.split('.');
+    let (header, payload, _signature) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let header = decode_part(header)?;
+    let payload = decode_part(payload)?;
+    let text = |map: &serde_json::Map<String, Value>, key: &str| {
+        map.get(key)
+            .and_then(Value::as_str)
+            .map(ToString::to_string)
+    };
+
+    Some(JwtClaims {
+        alg: text(&header, "alg"),
+        iss: text(&payload, "iss"),
+        sub: text(&payload, "sub"),
+        exp: payload.get("exp").and_then(Value::as_i64),
+    })
+}
+
+impl Detector for JwtDetector {
+    fn detections(&self) -> Vec<Detection> {
+        vec![self.detection.clone()]
+    }
+
+    fn detect(&self, command: &str) -> Vec<Finding> {
+        JWT.find_iter(command)
+            .filter(|m| decode(m.as_str()).is_some())
+            .map(|m| Finding {
+                detection: self.detection.clone(),
+                secret: m.as_str().to_string(),
+                span: m.range(),
+            })
+            .collect::<Vec<_>>()
+    }
+}
+
+#[cfg(test)]
+mod test_jwt {
+    use insta::assert_debug_snapshot;
+
+    use super::*;
+
+    const EXPIRED_JWT: &str = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.\
+                               eyJpc3MiOiJodHRwczovL2F1dGguZXhhbXBsZS5jb20iLCJzdWIiOiJ1c2VyLTEiLCJleHAiOjE1MTYyMzkwMjJ9.\
+                               c2lnbmF0dXJl";
+
+    #[test]
+    fn can_detect_jwt() {
+        let commands = [
+            format!("curl -H \"Authorization: Bearer {EXPIRED_JWT}\" https://api.example.com"),
+            "export TOKEN=eyJhbGciOiJub25lIn0.eyJzdWIiOiJhbm9uIn0.".to_string(),
+            "echo eyJhbGciOiJub25lIn0.eyJzdWIiOiJhbm9u.invalid".to_string(),
+        ];
+
+        let results = commands
+            .iter()
+            .map(|command| {
+                JwtDetector::default()
+                    .detect(command)
+                    .iter()
+                    .map(|f| (f.detection.id.clone(), decode(&f.secret)))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        assert_debug_snapshot!(results);
+    }
+}
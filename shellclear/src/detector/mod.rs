@@ -0,0 +1,30 @@
+pub mod encoded;
+mod flags;
+mod jwt;
+pub mod sequence;
+mod url;
+
+use std::ops::Range;
+
+pub use flags::{FlagsDetector, CLI_FLAGS};
+pub use jwt::{decode as decode_jwt, JwtDetector};
+pub use url::{UrlDetector, SENSITIVE_QUERY_PARAMS};
+
+use crate::data::Detection;
+
+/// Secret found by a detector
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub detection: Detection,
+    pub secret: String,
+    /// byte range of the secret in the scanned command
+    pub span: Range<usize>,
+}
+
+pub trait Detector: Send + Sync {
+    /// find secrets in the given command
+    fn detect(&self, command: &str) -> Vec<Finding>;
+
+    /// the detections that findings can have, e.g. to list and ignore them
+    fn detections(&self) -> Vec<Detection>;
+}
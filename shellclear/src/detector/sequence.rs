@@ -0,0 +1,201 @@
+use std::collections::HashSet;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{
+    data::{Detection, Severity},
+    lexer,
+};
+
+pub const TYPED_PASSWORD_ID: &str = "typed_password";
+/// commands that prompt for a password after they run
+const AUTH_PROMPTING: &[&str] = &["sudo", "su", "ssh", "mysql", "gpg"];
+/// how many lines after the auth prompting command are checked. a second
+/// line covers a retry after a wrong password
+const MAX_DISTANCE: usize = 2;
+const MIN_PASSWORD_LEN: usize = 6;
+const MAX_PASSWORD_LEN: usize = 64;
+/// shell builtins and programs that are often run with a version suffix or
+/// a target prefix, e.g. `python3.12`, `php8.2` or `aarch64-linux-gnu-gcc-13`.
+/// the programs run elsewhere in the history are known too
+const KNOWN_PROGRAMS: &[&str] = &[
+    "alias", "bg", "cd", "clear", "exit", "export", "fg", "history", "jobs", "logout", "popd",
+    "pushd", "pwd", "reset", "set", "source", "unalias", "unset", "c++", "clang", "clang++", "g++",
+    "gcc", "gem", "go", "java", "lua", "node", "npm", "perl", "php", "pip", "python", "ruby",
+    "tclsh", "wish",
+];
+
+lazy_static! {
+    /// lower case program name with an optional version, e.g. `gcc-13`
+    static ref PROGRAM_NAME: Regex = Regex::new(r"^([a-z][a-z0-9+_-]*?)-?(\d+(\.\d+)*)?$").unwrap();
+}
+
+#[must_use]
+pub fn detection() -> Detection {
+    Detection {
+        severity: Severity::High,
+        category: Some("credential".to_string()),
+        description: Some(
+            "Password typed at the shell prompt instead of the password prompt".to_string(),
+        ),
+        remediation: Some("Change the password and remove the line from history".to_string()),
+        ..Detection::new(
+            TYPED_PASSWORD_ID,
+            "Password Typed As Command",
+            Regex::new(".+").unwrap(),
+            0,
+        )
+    }
+}
+
+/// Find the indexes of commands that look like a password typed at the
+/// shell prompt after an auth prompting command (e.g. `sudo`) failed
+#[must_use]
+pub fn find_typed_passwords(commands: &[&str]) -> Vec<usize> {
+    let programs = commands
+        .iter()
+        .map(|command| programs(command))
+        .collect::<Vec<_>>();
+    // a password typed at the prompt is the program of its own line only
+    let is_known = |i: usize, name: &str| {
+        let names = program_names(name);
+        names.iter().any(|name| KNOWN_PROGRAMS.contains(name))
+            || programs
+                .iter()
+                .enumerate()
+                .any(|(j, programs)| j != i && names.iter().any(|name| programs.contains(*name)))
+    };
+
+    let mut found = vec![];
+    let mut last_auth: Option<usize> = None;
+    for (i, command) in commands.iter().enumerate() {
+        if is_auth_prompting(command) {
+            last_auth = Some(i);
+            continue;
+        }
+        match last_auth {
+            Some(auth)
+                if i - auth <= MAX_DISTANCE
+                    && is_typed_password(command)
+                    && !is_known(i, command.trim()) =>
+            {
+                found.push(i);
+            }
+            _ => last_auth = None,
+        }
+    }
+    found
+}
+
+/// The programs run by the simple commands of a command, with their names
+/// without a version
+fn programs(command: &str) -> HashSet<String> {
+    let tokens = lexer::tokenize(command);
+    lexer::simple_commands(&tokens)
+        .iter()
+        .filter_map(|simple_command| lexer::program(simple_command))
+        .flat_map(|(program, _)| {
+            program_names(&program)
+                .into_iter()
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// The word and, when it looks like a program name, the name without the
+/// version and the last part of a target prefixed name, e.g. `gcc-13`, `gcc`
+/// for `aarch64-linux-gnu-gcc-13`
+fn program_names(word: &str) -> Vec<&str> {
+    let mut names = vec![word];
+    if let Some(base) = PROGRAM_NAME.captures(word).and_then(|caps| caps.get(1)) {
+        let base = base.as_str();
+        names.push(base);
+        if let Some((_, last)) = base.rsplit_once('-') {
+            names.push(last);
+        }
+    }
+    names
+}
+
+fn is_auth_prompting(command: &str) -> bool {
+    let tokens = lexer::tokenize(command);
+    lexer::simple_commands(&tokens)
+        .iter()
+        .any(|simple_command| {
+            simple_command
+                .iter()
+                .take_while(|t| !t.value.starts_with('-'))
+                .any(|t| AUTH_PROMPTING.contains(&t.value.as_str()))
+                || lexer::program(simple_command)
+                    .is_some_and(|(program, _)| AUTH_PROMPTING.contains(&program.as_str()))
+        })
+}
+
+/// single token that mixes at least three character classes (lower, upper,
+/// digit, symbol)
+fn is_typed_password(command: &str) -> bool {
+    let command = command.trim();
+    if command.len() < MIN_PASSWORD_LEN
+        || command.len() > MAX_PASSWORD_LEN
+        || command.chars().any(char::is_whitespace)
+        || command.contains('/')
+        || command.starts_with('-')
+    {
+        return false;
+    }
+    let classes = [
+        command.chars().any(|c| c.is_ascii_lowercase()),
+        command.chars().any(|c| c.is_ascii_uppercase()),
+        command.chars().any(|c| c.is_ascii_digit()),
+        command.chars().any(|c| !c.is_ascii_alphanumeric()),
+    ];
+    classes.iter().filter(|c| **c).count() >= 3
+}
+
+#[cfg(test)]
+mod test_sequence {
+    use insta::assert_debug_snapshot;
+
+    use super::*;
+
+    #[test]
+    fn can_find_typed_passwords() {
+        let commands = [
+            "sudo apt update",
+            "hunter2!Xq",
+            "ls -la",
+            "Passw0rd",
+            "ssh deploy@10.0.0.1",
+            "S3cret#1",
+            "S3cret#2",
+            "S3cret#3",
+            "mysql -u root -p",
+            "ls",
+            "gpg --decrypt secrets.gpg",
+            "git status",
+            "su - admin",
+            "echo Hello1!",
+            // known programs, run elsewhere in the history or in the fixed list
+            "sudo make install",
+            "Build-2024",
+            "Build-2024 --release",
+            "sudo -i",
+            "python3.14",
+            "sudo apt install php8.3",
+            "php8.2",
+            "sudo make",
+            "aarch64-linux-gnu-gcc-13",
+            "sudo -v",
+            "pass-word1",
+        ];
+
+        let found = find_typed_passwords(&commands)
+            .iter()
+            .map(|i| commands[*i])
+            .collect::<Vec<_>>();
+
+        assert_debug_snapshot!(found);
+    }
+}
@@ -4,7 +4,7 @@ use lazy_static::lazy_static;
 use regex::Regex;
 
 use crate::{
-    data::Command,
+    data::{Command, Detection, SecretGroup},
     shell::{FishHistory, Shell},
 };
 
@@ -18,6 +18,13 @@ pub trait Exporter {
     ///
     /// Will return `Err` export has an error
     fn sensitive_data(&self, findings: &[Command]) -> Result<()>;
+
+    /// export findings grouped by the secret fingerprint
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` export has an error
+    fn secret_groups(&self, groups: &[SecretGroup]) -> Result<()>;
 }
 
 lazy_static! {
@@ -64,6 +71,122 @@ pub fn extract_time(finding: &Command) -> Result<String> {
     }
 }
 
+/// Describe the decoded JWT claims of the finding, e.g. `iss: auth.example.com,
+/// sub: user-1, exp: 2022-06-13 08:55:59 (expired)`
+#[must_use]
+pub fn jwt_details(finding: &Command) -> Vec<String> {
+    finding
+        .jwt_claims
+        .iter()
+        .map(|claims| {
+            let mut details = vec![];
+            if let Some(iss) = &claims.iss {
+                details.push(format!("iss: {iss}"));
+            }
+            if let Some(sub) = &claims.sub {
+                details.push(format!("sub: {sub}"));
+            }
+            match claims
+                .exp
+                .and_then(|exp| Utc.timestamp_opt(exp, 0).single())
+            {
+                Some(exp) => details.push(format!(
+                    "exp: {} ({})",
+                    exp.format(DATE_TIME_FORMAT),
+                    if exp < Utc::now() { "expired" } else { "live" }
+                )),
+                None => details.push("exp: never".to_string()),
+            }
+            details.join(", ")
+        })
+        .collect::<Vec<_>>()
+}
+
+/// Describe the detection metadata, e.g. `severity: high, category: vcs, tags:
+/// github`, the description and how to rotate the secret
+#[must_use]
+pub fn detection_details(detection: &Detection) -> Vec<String> {
+    let mut metadata = vec![format!("severity: {}", detection.severity)];
+    if let Some(category) = &detection.category {
+        metadata.push(format!("category: {category}"));
+    }
+    if !detection.tags.is_empty() {
+        metadata.push(format!("tags: {}", detection.tags.join(", ")));
+    }
+
+    let mut details = vec![metadata.join(", ")];
+    if let Some(description) = &detection.description {
+        details.push(description.clone());
+    }
+    if let Some(remediation) = &detection.remediation {
+        details.push(format!("Remediation: {remediation}"));
+    }
+    details
+}
+
+/// Group the findings of the same credential incident, keeping the order of the
+/// first finding of every incident
+#[must_use]
+pub fn group_incidents(findings: &[Command]) -> Vec<Vec<&Command>> {
+    let mut groups: Vec<Vec<&Command>> = vec![];
+    for finding in findings {
+        let group = finding
+            .incident
+            .and_then(|incident| groups.iter_mut().find(|g| g[0].incident == Some(incident)));
+        match group {
+            Some(group) => group.push(finding),
+            None => groups.push(vec![finding]),
+        }
+    }
+    groups
+}
+
+/// Describe a credential incident, e.g. `Credential incident: aws (2 findings)`
+#[must_use]
+pub fn incident_title(group: &[&Command]) -> Option<String> {
+    if group.len() < 2 {
+        return None;
+    }
+    let mut vendors = group
+        .iter()
+        .flat_map(|c| c.detections.iter().filter_map(|d| d.vendor.clone()))
+        .collect::<Vec<_>>();
+    vendors.sort();
+    vendors.dedup();
+    Some(format!(
+        "Credential incident: {} ({} findings)",
+        vendors.join(", "),
+        group.len()
+    ))
+}
+
+/// Describe when the secret was seen, e.g. `2022-06-13 08:55:59 - 2022-06-28
+/// 17:52:40`
+#[must_use]
+pub fn seen_range(group: &SecretGroup) -> String {
+    let format = |timestamp: Option<i64>| {
+        timestamp
+            .and_then(|t| Utc.timestamp_opt(t, 0).single())
+            .map(|t| t.format(DATE_TIME_FORMAT).to_string())
+    };
+    match (format(group.first_seen), format(group.last_seen)) {
+        (Some(first), Some(last)) if first == last => first,
+        (Some(first), Some(last)) => format!("{first} - {last}"),
+        _ => String::new(),
+    }
+}
+
+/// Shells that the secret was seen in, e.g. `bash, zshrc`
+#[must_use]
+pub fn shells_names(group: &SecretGroup) -> String {
+    group
+        .shells
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 fn convert_str_timestamp_to_date_time(timestamp: &str) -> Result<DateTime<Utc>> {
     Ok(Utc.from_utc_datetime(&NaiveDateTime::from_timestamp(timestamp.parse::<i64>()?, 0)))
 }
@@ -75,7 +198,10 @@ mod test_exporter {
     use insta::assert_debug_snapshot;
 
     use super::*;
-    use crate::shell::Shell;
+    use crate::{
+        data::{FormatCheck, JwtClaims},
+        shell::Shell,
+    };
 
     #[test]
     fn can_extract_time_zshrc() {
@@ -85,6 +211,11 @@ mod test_exporter {
             command: "test command".to_string(),
             data: ": 1655110559:0;command data".to_string(),
             secrets: vec![],
+            spans: vec![],
+            jwt_claims: vec![],
+            format_check: FormatCheck::Unchecked,
+            incident: None,
+            fingerprints: vec![],
         };
 
         let resp = extract_time(&shell_finding);
@@ -102,10 +233,47 @@ mod test_exporter {
 }"#
             .to_string(),
             secrets: vec![],
+            spans: vec![],
+            jwt_claims: vec![],
+            format_check: FormatCheck::Unchecked,
+            incident: None,
+            fingerprints: vec![],
         };
 
         let resp = extract_time(&shell_finding);
 
         assert_debug_snapshot!(resp);
     }
+
+    #[test]
+    fn can_get_jwt_details() {
+        let shell_finding = Command {
+            shell_type: Shell::Bash,
+            detections: vec![],
+            command: "test command".to_string(),
+            data: "test command".to_string(),
+            secrets: vec![],
+            spans: vec![],
+            jwt_claims: vec![
+                JwtClaims {
+                    alg: Some("HS256".to_string()),
+                    iss: Some("https://auth.example.com".to_string()),
+                    sub: Some("user-1".to_string()),
+                    exp: Some(1_516_239_022),
+                },
+                JwtClaims {
+                    alg: Some("RS256".to_string()),
+                    iss: None,
+                    sub: Some("deploy".to_string()),
+                    exp: Some(4_102_444_800),
+                },
+                JwtClaims::default(),
+            ],
+            format_check: FormatCheck::Unchecked,
+            incident: None,
+            fingerprints: vec![],
+        };
+
+        assert_debug_snapshot!(jwt_details(&shell_finding));
+    }
 }
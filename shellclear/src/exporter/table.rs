@@ -4,8 +4,11 @@ use anyhow::Result;
 use prettytable::{Cell, Row};
 
 use crate::{
-    data::Command,
-    exporter::data::{chunk, extract_time, Exporter, LIMIT_COMMAND},
+    data::{Command, SecretGroup},
+    exporter::data::{
+        chunk, extract_time, group_incidents, incident_title, jwt_details, seen_range,
+        shells_names, Exporter, LIMIT_COMMAND,
+    },
 };
 
 #[derive(Default)]
@@ -18,9 +21,48 @@ impl Exporter for Table {
         print!("{}", str::from_utf8(&out)?);
         Ok(())
     }
+
+    fn secret_groups(&self, groups: &[SecretGroup]) -> Result<()> {
+        let mut out = Vec::new();
+        Self::prepare_secret_groups(&mut out, groups)?;
+        print!("{}", str::from_utf8(&out)?);
+        Ok(())
+    }
 }
 
 impl Table {
+    fn prepare_secret_groups(out: &mut Vec<u8>, groups: &[SecretGroup]) -> Result<()> {
+        if groups.is_empty() {
+            return Ok(());
+        }
+        let mut table = prettytable::Table::new();
+
+        table.add_row(Row::new(vec![
+            Cell::new("#"),
+            Cell::new("Fingerprint"),
+            Cell::new("Name"),
+            Cell::new("Count"),
+            Cell::new("Shells"),
+            Cell::new("Sources"),
+            Cell::new("Seen"),
+        ]));
+
+        for (i, group) in groups.iter().enumerate() {
+            table.add_row(Row::new(vec![
+                Cell::new(&format!("{}", i + 1)),
+                Cell::new(&group.fingerprint),
+                Cell::new(&group.names.join("\r\n")),
+                Cell::new(&format!("{}", group.count)),
+                Cell::new(&shells_names(group)),
+                Cell::new(&group.sources.join("\r\n")),
+                Cell::new(&seen_range(group)),
+            ]));
+        }
+
+        table.print(out)?;
+        Ok(())
+    }
+
     fn prepare_sensitive_data(out: &mut Vec<u8>, findings: &[Command]) -> Result<()> {
         let mut table = prettytable::Table::new();
 
@@ -28,28 +70,49 @@ impl Table {
             Cell::new("#"),
             Cell::new("Shell"),
             Cell::new("Time"),
+            Cell::new("Severity"),
             Cell::new("Name"),
             Cell::new("Command"),
         ]));
 
         let mut count = 0;
-        let rows = findings
-            .iter()
-            .map(|f| {
+        let rows = group_incidents(findings)
+            .into_iter()
+            .map(|group| {
                 count += 1;
+                let names = incident_title(&group)
+                    .into_iter()
+                    .chain(group.iter().flat_map(|f| {
+                        f.detections
+                            .iter()
+                            .flat_map(|d| {
+                                std::iter::once(d.name.clone()).chain(
+                                    d.remediation.iter().map(|r| format!("Remediation: {r}")),
+                                )
+                            })
+                            .chain(jwt_details(f).into_iter().map(|d| format!("JWT {d}")))
+                    }))
+                    .collect::<Vec<_>>();
                 vec![
                     Cell::new(&format!("{count:?}")),
-                    Cell::new(&format!("{:?}", f.shell_type)),
-                    Cell::new(&extract_time(f).unwrap_or_else(|_| String::new())),
+                    Cell::new(&format!("{:?}", group[0].shell_type)),
+                    Cell::new(&extract_time(group[0]).unwrap_or_else(|_| String::new())),
                     Cell::new(
-                        f.detections
+                        &group
+                            .iter()
+                            .flat_map(|f| f.detections.iter().map(|d| d.severity))
+                            .max()
+                            .map(|s| s.to_string())
+                            .unwrap_or_default(),
+                    ),
+                    Cell::new(names.join("\r\n").as_ref()),
+                    Cell::new(
+                        &group
                             .iter()
-                            .map(|f| f.name.clone())
+                            .map(|f| chunk(&f.command, LIMIT_COMMAND))
                             .collect::<Vec<_>>()
-                            .join("\r\n")
-                            .as_ref(),
+                            .join("\r\n"),
                     ),
-                    Cell::new(&chunk(&f.command, LIMIT_COMMAND)),
                 ]
             })
             .collect::<Vec<_>>();
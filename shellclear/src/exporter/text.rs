@@ -4,8 +4,11 @@ use anyhow::Result;
 use console::style;
 
 use crate::{
-    data::Command,
-    exporter::data::{chunk, extract_time, Exporter, LIMIT_COMMAND},
+    data::{Command, FormatCheck, SecretGroup},
+    exporter::data::{
+        chunk, detection_details, extract_time, group_incidents, incident_title, jwt_details,
+        seen_range, shells_names, Exporter, LIMIT_COMMAND,
+    },
 };
 
 #[derive(Default)]
@@ -18,34 +21,107 @@ impl Exporter for Text {
         print!("{}", str::from_utf8(&out)?);
         Ok(())
     }
+
+    fn secret_groups(&self, groups: &[SecretGroup]) -> Result<()> {
+        let mut out = Vec::new();
+        Self::prepare_secret_groups(&mut out, groups)?;
+        print!("{}", str::from_utf8(&out)?);
+        Ok(())
+    }
 }
 
 impl Text {
     fn prepare_sensitive_data(out: &mut Vec<u8>, findings: &[Command]) -> Result<()> {
         let mut count = 0;
-        for f in findings.iter() {
+        for group in group_incidents(findings) {
             count += 1;
-            let finding_names = f
-                .detections
-                .iter()
-                .map(|f| f.name.clone())
-                .collect::<Vec<_>>()
-                .join(", ");
-
-            let title = format!(
-                "{}. [{}] {} {}",
-                count,
-                f.shell_type,
-                finding_names,
-                extract_time(f).unwrap_or_else(|_| String::new())
-            );
-
-            writeln!(out, "{}", style(title).bold())?;
-            writeln!(out, "{}", chunk(&f.command, LIMIT_COMMAND))?;
+            if let Some(incident) = incident_title(&group) {
+                writeln!(
+                    out,
+                    "{}",
+                    style(format!("{}. [{}] {}", count, group[0].shell_type, incident)).bold()
+                )?;
+                for f in group {
+                    Self::write_finding(out, &format!("-  {}", Self::title(f)), f)?;
+                }
+            } else {
+                let f = group[0];
+                Self::write_finding(
+                    out,
+                    &format!("{}. [{}] {}", count, f.shell_type, Self::title(f)),
+                    f,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn prepare_secret_groups(out: &mut Vec<u8>, groups: &[SecretGroup]) -> Result<()> {
+        for (i, group) in groups.iter().enumerate() {
+            writeln!(
+                out,
+                "{}",
+                style(format!(
+                    "{}. {} {}",
+                    i + 1,
+                    group.fingerprint,
+                    group.names.join(", ")
+                ))
+                .bold()
+            )?;
+            writeln!(
+                out,
+                "seen {} times in {} {}",
+                group.count,
+                shells_names(group),
+                style(seen_range(group)).dim()
+            )?;
+            for source in &group.sources {
+                writeln!(out, "- {source}")?;
+            }
             writeln!(out)?;
         }
         Ok(())
     }
+
+    fn title(f: &Command) -> String {
+        let finding_names = f
+            .detections
+            .iter()
+            .map(|f| f.name.clone())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut title = format!(
+            "{} {}",
+            finding_names,
+            extract_time(f).unwrap_or_else(|_| String::new())
+        );
+        match f.format_check {
+            FormatCheck::Confirmed => title.push_str(" (confirmed format)"),
+            FormatCheck::Mismatch => title.push_str(" (format mismatch)"),
+            FormatCheck::Unchecked => {}
+        }
+        title
+    }
+
+    fn write_finding(out: &mut Vec<u8>, title: &str, f: &Command) -> Result<()> {
+        writeln!(out, "{}", style(title).bold())?;
+        for detection in &f.detections {
+            let details = detection_details(detection);
+            writeln!(
+                out,
+                "{}",
+                style(format!("{}: {}", detection.name, details.join(". "))).dim()
+            )?;
+        }
+        for details in jwt_details(f) {
+            writeln!(out, "JWT {}", style(details).dim())?;
+        }
+        writeln!(out, "{}", chunk(&f.command, LIMIT_COMMAND))?;
+        writeln!(out)?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -65,22 +141,17 @@ mod test_exporter_text {
         let shell_finding = Command {
             shell_type: Shell::Zshrc,
             detections: vec![
-                Detection {
-                    test: Regex::new("test").unwrap(),
-                    name: "test name".to_string(),
-                    id: String::new(),
-                    secret_group: 0,
-                },
-                Detection {
-                    test: Regex::new("test2").unwrap(),
-                    name: "test name2".to_string(),
-                    id: String::new(),
-                    secret_group: 0,
-                },
+                Detection::new("", "test name", Regex::new("test").unwrap(), 0),
+                Detection::new("", "test name2", Regex::new("test2").unwrap(), 0),
             ],
             command: "test command".to_string(),
             data: ": 1655110559:0;command data".to_string(),
             secrets: vec![],
+            spans: vec![],
+            jwt_claims: vec![],
+            format_check: FormatCheck::Unchecked,
+            incident: None,
+            fingerprints: vec![],
         };
 
         let findings = vec![shell_finding];
@@ -93,4 +164,46 @@ mod test_exporter_text {
             .replace("\u{1b}[1m", "")
             .replace("\u{1b}[0m", ""));
     }
+
+    #[test]
+    fn can_prepare_incident() {
+        let mut out = Vec::new();
+
+        let finding = |command: &str, id: &str, name: &str| {
+            let mut detection = Detection::new(id, name, Regex::new("test").unwrap(), 0);
+            detection.vendor = Some("aws".to_string());
+            Command {
+                shell_type: Shell::Bash,
+                detections: vec![detection],
+                command: command.to_string(),
+                data: command.to_string(),
+                secrets: vec![],
+                spans: vec![],
+                jwt_claims: vec![],
+                format_check: FormatCheck::Unchecked,
+                incident: Some(3),
+                fingerprints: vec![],
+            }
+        };
+
+        let findings = vec![
+            finding(
+                "export AWS_ACCESS_KEY_ID=AK******",
+                "aws_end_access_key",
+                "AWS Env Access Key",
+            ),
+            finding(
+                "export AWS_SECRET_ACCESS_KEY=wJ******",
+                "aws_env_secret_key",
+                "AWS Env Secret Key",
+            ),
+        ];
+        Text::prepare_sensitive_data(&mut out, &findings).unwrap();
+
+        assert_debug_snapshot!(str::from_utf8(&out)
+            .unwrap()
+            .replace("\r\n", "\n")
+            .replace("\u{1b}[1m", "")
+            .replace("\u{1b}[0m", ""));
+    }
 }
@@ -0,0 +1,210 @@
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Result};
+use sha2::{Digest, Sha256};
+
+use crate::data::{Baseline, Command, SecretGroup};
+
+/// Stable fingerprint of a secret found by a pattern. the salt keeps the
+/// fingerprint from being used to brute force short secrets
+#[must_use]
+pub fn fingerprint(salt: &str, pattern_id: &str, secret: &str) -> String {
+    let mut hasher = Sha256::new();
+    for part in [salt, pattern_id, secret] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    hasher.finalize()[..8]
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// New random salt for fingerprints
+///
+/// # Errors
+///
+/// Will return `Err` when the system random source is not available
+pub fn new_salt() -> Result<String> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|e| anyhow!("could not create salt: {e}"))?;
+    Ok(bytes.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+/// Salt for the fingerprints of a single run, that are only compared in
+/// memory. without a system random source it is made from the time and the
+/// process id
+#[must_use]
+pub fn session_salt() -> String {
+    new_salt().unwrap_or_else(|e| {
+        log::warn!("{e}, the fingerprints are salted with the time");
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos());
+        fingerprint(
+            &nanos.to_string(),
+            "session",
+            &std::process::id().to_string(),
+        )
+    })
+}
+
+/// Fingerprints of the secrets of the command with the given salt
+#[must_use]
+pub fn fingerprints(salt: &str, command: &Command) -> Vec<String> {
+    command
+        .secrets
+        .iter()
+        .zip(&command.detections)
+        .map(|(secret, detection)| fingerprint(salt, &detection.id, secret))
+        .collect()
+}
+
+/// Is every secret of the command accepted in the baseline
+#[must_use]
+pub fn is_in_baseline(command: &Command, baseline: &Baseline) -> bool {
+    match &baseline.salt {
+        Some(salt) => fingerprints(salt, command)
+            .iter()
+            .all(|f| baseline.fingerprints.contains(f)),
+        None => command
+            .fingerprints
+            .iter()
+            .all(|f| baseline.fingerprints.contains(f)),
+    }
+}
+
+/// Group the findings by the secret fingerprint, the most reused secrets first.
+/// every finding comes with its source, e.g. the history file path
+#[must_use]
+pub fn group_by_secret<'a>(
+    findings: impl IntoIterator<Item = (&'a str, &'a Command)>,
+) -> Vec<SecretGroup> {
+    let mut groups: Vec<SecretGroup> = vec![];
+    let mut index: HashMap<&str, usize> = HashMap::new();
+
+    for (source, command) in findings {
+        let timestamp = command.timestamp();
+        for (fingerprint, detection) in command.fingerprints.iter().zip(&command.detections) {
+            let i = *index.entry(fingerprint).or_insert_with(|| {
+                groups.push(SecretGroup {
+                    fingerprint: fingerprint.clone(),
+                    names: vec![],
+                    count: 0,
+                    shells: vec![],
+                    sources: vec![],
+                    first_seen: None,
+                    last_seen: None,
+                });
+                groups.len() - 1
+            });
+            let group = &mut groups[i];
+            group.count += 1;
+            if !group.names.contains(&detection.name) {
+                group.names.push(detection.name.clone());
+            }
+            if !group.shells.contains(&command.shell_type) {
+                group.shells.push(command.shell_type.clone());
+            }
+            if !group.sources.iter().any(|s| s == source) {
+                group.sources.push(source.to_string());
+            }
+            if let Some(timestamp) = timestamp {
+                group.first_seen = Some(group.first_seen.map_or(timestamp, |t| t.min(timestamp)));
+                group.last_seen = Some(group.last_seen.map_or(timestamp, |t| t.max(timestamp)));
+            }
+        }
+    }
+
+    groups.sort_by_key(|g| std::cmp::Reverse(g.count));
+    groups
+}
+
+#[cfg(test)]
+mod test_fingerprint {
+    use insta::assert_debug_snapshot;
+    use regex::Regex;
+
+    use super::*;
+    use crate::{
+        data::{Detection, FormatCheck},
+        shell::Shell,
+    };
+
+    fn command(shell_type: Shell, data: &str, secret: &str) -> Command {
+        Command {
+            shell_type,
+            detections: vec![Detection::new(
+                "github_env_token",
+                "GitHub Env Token",
+                Regex::new("test").unwrap(),
+                0,
+            )],
+            command: data.to_string(),
+            data: data.to_string(),
+            secrets: vec![secret.to_string()],
+            spans: vec![],
+            jwt_claims: vec![],
+            format_check: FormatCheck::Unchecked,
+            incident: None,
+            fingerprints: vec![fingerprint("salt", "github_env_token", secret)],
+        }
+    }
+
+    #[test]
+    fn can_fingerprint() {
+        assert_debug_snapshot!(fingerprint("salt", "github_env_token", "token"));
+        assert_ne!(
+            fingerprint("salt", "github_env_token", "token"),
+            fingerprint("pepper", "github_env_token", "token")
+        );
+    }
+
+    #[test]
+    fn can_match_baseline_with_its_salt() {
+        let command = command(Shell::Bash, "export GITHUB_TOKEN=a", "a");
+        let salted = |salt: &str| Baseline {
+            salt: Some(salt.to_string()),
+            fingerprints: fingerprints(salt, &command),
+        };
+        // the baseline salt is used, not the salt the command was fingerprinted with
+        assert_debug_snapshot!((
+            is_in_baseline(&command, &salted("pepper")),
+            is_in_baseline(
+                &command,
+                &Baseline {
+                    salt: None,
+                    fingerprints: command.fingerprints.clone(),
+                }
+            ),
+            is_in_baseline(
+                &command,
+                &Baseline {
+                    salt: Some("pepper".to_string()),
+                    fingerprints: command.fingerprints.clone(),
+                }
+            ),
+        ));
+    }
+
+    #[test]
+    fn can_group_by_secret() {
+        let commands = [
+            command(Shell::Zshrc, ": 1655110559:0;export GITHUB_TOKEN=a", "a"),
+            command(Shell::Bash, "export GITHUB_TOKEN=b", "b"),
+            command(Shell::Bash, "export GITHUB_TOKEN=a", "a"),
+            command(Shell::Zshrc, ": 1656438760:0;export GITHUB_TOKEN=a", "a"),
+        ];
+        let sources = [
+            "/home/alice/.zsh_history",
+            "/home/alice/.bash_history",
+            "/home/bob/.bash_history",
+            "/home/alice/.zsh_history",
+        ];
+
+        assert_debug_snapshot!(group_by_secret(sources.into_iter().zip(&commands)));
+    }
+}
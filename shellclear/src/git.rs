@@ -0,0 +1,300 @@
+use std::{collections::HashSet, path::Path};
+
+use anyhow::Result;
+use chrono::{TimeZone, Utc};
+use git2::{ObjectType, Oid, Repository, Sort, TreeWalkMode, TreeWalkResult};
+use log::{debug, warn};
+
+use crate::{data::Command, engine::PatternsEngine, image::HISTORY_FILES, shell::Shell};
+
+/// shell rc file names, scanned line by line
+const RC_FILES: &[(&str, Shell)] = &[
+    (".bashrc", Shell::Bash),
+    (".bash_profile", Shell::Bash),
+    (".bash_login", Shell::Bash),
+    (".bash_aliases", Shell::Bash),
+    (".profile", Shell::Bash),
+    (".zshrc", Shell::Zshrc),
+    (".zshenv", Shell::Zshrc),
+    (".zprofile", Shell::Zshrc),
+    (".zlogin", Shell::Zshrc),
+    ("config.fish", Shell::Fish),
+    ("Microsoft.PowerShell_profile.ps1", Shell::PowerShell),
+    ("profile.ps1", Shell::PowerShell),
+];
+
+/// A version of a history or rc file in the commit history of a repository
+#[derive(Debug, Clone)]
+pub struct GitFile {
+    /// first commit with this version of the file
+    pub commit: String,
+    /// commit time as unix timestamp
+    pub time: i64,
+    /// path in the repository
+    pub path: String,
+    pub shell: Shell,
+    /// rc files are scanned line by line, history files in the shell format
+    pub rc: bool,
+    pub content: String,
+}
+
+/// A sensitive command in the commit history of a repository
+#[derive(Debug, Clone)]
+pub struct GitFinding {
+    /// first commit with the command in the file
+    pub commit: String,
+    pub time: i64,
+    pub path: String,
+    /// line of the command in the file, starting at 1
+    pub line: usize,
+    pub command: Command,
+}
+
+impl GitFinding {
+    /// `commit <short id> (<date>) <path>`, the findings of a file in a commit
+    /// share the title
+    #[must_use]
+    pub fn title(&self) -> String {
+        let date = Utc
+            .timestamp_opt(self.time, 0)
+            .single()
+            .map_or_else(String::new, |t| format!(" ({})", t.format("%Y-%m-%d")));
+        format!("commit {}{} {}", self.short_commit(), date, self.path)
+    }
+
+    /// `commit <short id> <path> line <line>`
+    #[must_use]
+    pub fn source(&self) -> String {
+        format!(
+            "commit {} {} line {}",
+            self.short_commit(),
+            self.path,
+            self.line
+        )
+    }
+
+    fn short_commit(&self) -> &str {
+        &self.commit[..self.commit.len().min(7)]
+    }
+}
+
+/// Read every version of the history and rc files in all the commits
+/// reachable from the references of a local repository, oldest commit first.
+/// a version is reported once, with the first commit that has it
+///
+/// # Errors
+///
+/// Will return `Err` when the repository can not be opened or read
+pub fn scan(path: &Path) -> Result<Vec<GitFile>> {
+    let repo = Repository::open(path)?;
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+    revwalk.push_glob("*")?;
+    // a repository without commits has no HEAD, a detached HEAD has no reference
+    if repo.head().is_ok() {
+        revwalk.push_head()?;
+    }
+
+    let mut seen = HashSet::new();
+    let mut files = vec![];
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        let mut blobs: Vec<(String, Oid, Shell, bool)> = vec![];
+        commit.tree()?.walk(TreeWalkMode::PreOrder, |root, entry| {
+            if entry.kind() != Some(ObjectType::Blob) {
+                return TreeWalkResult::Ok;
+            }
+            let Some(name) = entry.name() else {
+                return TreeWalkResult::Ok;
+            };
+            let kind = HISTORY_FILES
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, shell)| (shell, false))
+                .or_else(|| {
+                    RC_FILES
+                        .iter()
+                        .find(|(n, _)| *n == name)
+                        .map(|(_, shell)| (shell, true))
+                });
+            if let Some((shell, rc)) = kind {
+                blobs.push((format!("{root}{name}"), entry.id(), shell.clone(), rc));
+            }
+            TreeWalkResult::Ok
+        })?;
+
+        for (path, oid, shell, rc) in blobs {
+            if !seen.insert((path.clone(), oid)) {
+                continue;
+            }
+            let blob = repo.find_blob(oid)?;
+            files.push(GitFile {
+                commit: commit.id().to_string(),
+                time: commit.time().seconds(),
+                path,
+                shell,
+                rc,
+                content: String::from_utf8_lossy(blob.content()).to_string(),
+            });
+        }
+    }
+    debug!(
+        "found {} history and rc file versions in repository {}",
+        files.len(),
+        path.display()
+    );
+    Ok(files)
+}
+
+/// Find the sensitive commands of the file versions. a secret is reported once
+/// per path, with the first commit that adds it to the file. a history version
+/// that can not be parsed is skipped with a warning
+#[must_use]
+pub fn find(engine: &PatternsEngine, files: &[GitFile]) -> Vec<GitFinding> {
+    let mut reported = HashSet::new();
+    let mut findings = vec![];
+    for file in files {
+        let (commands, lines) = if file.rc {
+            let commands = engine.find_lines_commands(&file.shell, &file.content);
+            let lines = (1..=commands.len()).collect::<Vec<_>>();
+            (commands, lines)
+        } else {
+            // a malformed history version does not stop the scan of the others
+            let commands = match engine.find_content_commands(&file.shell, &file.content) {
+                Ok(commands) => commands,
+                Err(e) => {
+                    warn!(
+                        "could not read {} in commit {}: {}",
+                        file.path, file.commit, e
+                    );
+                    continue;
+                }
+            };
+            (commands, command_lines(&file.shell, &file.content))
+        };
+        for (command, line) in commands.into_iter().zip(lines) {
+            // the fingerprints are compared since the command is masked, two
+            // secrets can have the same masked command
+            let mut new_secret = false;
+            for fingerprint in &command.fingerprints {
+                new_secret |= reported.insert((file.path.clone(), fingerprint.clone()));
+            }
+            if command.detections.is_empty() || !new_secret {
+                continue;
+            }
+            findings.push(GitFinding {
+                commit: file.commit.clone(),
+                time: file.time,
+                path: file.path.clone(),
+                line,
+                command,
+            });
+        }
+    }
+    findings
+}
+
+/// Lines of the history commands, a fish history entry starts with `- cmd:`
+fn command_lines(shell: &Shell, content: &str) -> Vec<usize> {
+    match shell {
+        Shell::Fish => content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| line.starts_with("- cmd:"))
+            .map(|(index, _)| index + 1)
+            .collect(),
+        _ => (1..=content.lines().count()).collect(),
+    }
+}
+
+#[cfg(test)]
+mod test_git {
+    use std::fs;
+
+    use git2::{Signature, Time};
+    use insta::assert_debug_snapshot;
+    use tempdir::TempDir;
+
+    use super::*;
+    use crate::config::Config;
+
+    fn commit(repo: &Repository, files: &[(&str, Option<&str>)], time: i64) {
+        let workdir = repo.workdir().unwrap();
+        let mut index = repo.index().unwrap();
+        for (path, content) in files {
+            if let Some(content) = content {
+                let file = workdir.join(path);
+                fs::create_dir_all(file.parent().unwrap()).unwrap();
+                fs::write(file, content).unwrap();
+                index.add_path(Path::new(path)).unwrap();
+            } else {
+                fs::remove_file(workdir.join(path)).unwrap();
+                index.remove_path(Path::new(path)).unwrap();
+            }
+        }
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::new("test", "test@example.com", &Time::new(time, 0)).unwrap();
+        let parent = repo.head().ok().map(|h| h.peel_to_commit().unwrap());
+        let parents = parent.iter().collect::<Vec<_>>();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "update",
+            &tree,
+            &parents,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn can_find_secrets_in_commit_history() {
+        let temp_dir = TempDir::new("git").unwrap();
+        let repo_path = temp_dir.path().join("dotfiles");
+        let repo = Repository::init(&repo_path).unwrap();
+        commit(
+            &repo,
+            &[
+                ("bash/.bash_history", Some("ls\nexport FIND_ME=history\n")),
+                (".zshrc", Some("export FIND_ME=first\n")),
+                ("README.md", Some("export FIND_ME=readme\n")),
+                (".profile", Some("export FIND_ME=alpha01\n")),
+                // skipped, the other files are still scanned
+                ("fish/fish_history", Some("- cmd: [export FIND_ME=broken\n")),
+            ],
+            1_600_000_000,
+        );
+        commit(
+            &repo,
+            &[
+                ("bash/.bash_history", None),
+                (".profile", Some("export FIND_ME=alpha02\n")),
+                (
+                    ".zshrc",
+                    Some("alias ll='ls -l'\nexport FIND_ME=first\nexport FIND_ME=second\n"),
+                ),
+            ],
+            1_700_000_000,
+        );
+
+        let config = Config::with_custom_path(&temp_dir.path().join("app"));
+        config.init().unwrap();
+        fs::write(
+            &config.sensitive_commands_path,
+            "- name: Find Me\n  test: FIND_ME=(\\w+)\n  secret_group: 1\n  id: find_me\n",
+        )
+        .unwrap();
+        fs::write(&config.fingerprint_salt_path, "salt").unwrap();
+        let engine = PatternsEngine::with_config(&config).unwrap();
+
+        let files = scan(&repo_path).unwrap();
+        let findings = find(&engine, &files)
+            .iter()
+            .map(|f| (f.title(), f.source(), f.command.command.clone()))
+            .collect::<Vec<_>>();
+        assert_debug_snapshot!(findings);
+        assert_debug_snapshot!(scan(&temp_dir.path().join("missing")).is_err());
+        temp_dir.close().unwrap();
+    }
+}
@@ -0,0 +1,505 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+};
+
+use anyhow::{anyhow, Result};
+use flate2::read::GzDecoder;
+use log::{debug, warn};
+use serde_derive::Deserialize;
+
+use crate::shell::Shell;
+
+/// `docker save` manifest
+const DOCKER_MANIFEST: &str = "manifest.json";
+/// OCI image layout index
+const OCI_INDEX: &str = "index.json";
+/// content addressed manifests, configs and layers
+const BLOBS_FOLDER: &str = "blobs/";
+/// manifests, indexes and configs are read in the first pass over the tarball
+const MAX_METADATA_SIZE: u64 = 1024 * 1024;
+/// a file in a lower layer with the name after the prefix is deleted
+const WHITEOUT_PREFIX: &str = ".wh.";
+/// the lower layers content of the directory is hidden
+const OPAQUE_WHITEOUT: &str = ".wh..wh..opq";
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// history file names, `ash` and `sh` histories have the bash format
+pub(crate) const HISTORY_FILES: &[(&str, Shell)] = &[
+    (".bash_history", Shell::Bash),
+    (".ash_history", Shell::Bash),
+    (".sh_history", Shell::Bash),
+    (".zsh_history", Shell::Zshrc),
+    ("fish_history", Shell::Fish),
+    ("ConsoleHost_history.txt", Shell::PowerShell),
+];
+
+/// A history file of an image layer
+#[derive(Debug, Clone)]
+pub struct ImageHistory {
+    /// digest of the layer, e.g. `sha256:...`
+    pub layer: String,
+    /// absolute path in the image, e.g. `/root/.bash_history`
+    pub path: String,
+    pub shell: Shell,
+    pub content: String,
+    /// digest of an upper layer that deletes or replaces the file. the file is
+    /// still readable from its layer
+    pub removed_by: Option<String>,
+}
+
+impl ImageHistory {
+    /// `layer <digest> <path>`, with the layer that removes the file
+    #[must_use]
+    pub fn title(&self) -> String {
+        match &self.removed_by {
+            Some(removed_by) => format!(
+                "layer {} {} (removed in layer {})",
+                self.layer, self.path, removed_by
+            ),
+            None => format!("layer {} {}", self.layer, self.path),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DockerManifest {
+    config: Option<String>,
+    layers: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct DockerConfig {
+    rootfs: Option<DockerRootfs>,
+}
+
+#[derive(Deserialize)]
+struct DockerRootfs {
+    #[serde(default)]
+    diff_ids: Vec<String>,
+}
+
+/// OCI index or manifest, an index lists manifests and a manifest lists
+/// layers
+#[derive(Deserialize)]
+struct OciManifest {
+    #[serde(default)]
+    manifests: Vec<OciDescriptor>,
+    #[serde(default)]
+    layers: Vec<OciDescriptor>,
+}
+
+#[derive(Deserialize)]
+struct OciDescriptor {
+    digest: String,
+}
+
+/// A layer of an image, in the order of the image layers
+struct Layer {
+    /// tarball entry of the layer
+    entry: String,
+    digest: String,
+}
+
+/// The entries of a layer that matter for the history files
+#[derive(Default)]
+struct LayerScan {
+    /// path, shell and content of the history files
+    histories: Vec<(String, Shell, String)>,
+    /// paths of any entry type with a history file name
+    history_paths: HashSet<String>,
+    /// paths deleted from the lower layers
+    whiteouts: Vec<String>,
+    /// directories whose lower layers content is hidden
+    opaque_dirs: Vec<String>,
+}
+
+impl LayerScan {
+    /// Does the layer delete or replace the path of a lower layer
+    fn removes(&self, path: &str) -> bool {
+        self.history_paths.contains(path)
+            || self.whiteouts.iter().any(|w| is_in(path, w))
+            || self
+                .opaque_dirs
+                .iter()
+                .any(|d| d.is_empty() || is_in(path, d))
+    }
+}
+
+/// Is the path the given path or inside it
+fn is_in(path: &str, dir: &str) -> bool {
+    path == dir
+        || path
+            .strip_prefix(dir)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Read the history files of all the layers of a `docker save` or OCI image
+/// layout tarball, in the order of the image layers. a history file that is
+/// deleted or replaced by an upper layer is reported with that layer
+///
+/// # Errors
+///
+/// Will return `Err` when the tarball could not be read or has no image
+/// manifest
+pub fn scan(path: &Path) -> Result<Vec<ImageHistory>> {
+    // manifest.json is often the last entry, so the layers are read in a second
+    // pass
+    let metadata = read_metadata(path)?;
+    let images = image_layers(&metadata)?;
+
+    let entries = images
+        .iter()
+        .flatten()
+        .map(|l| l.entry.as_str())
+        .collect::<HashSet<_>>();
+    let mut scans: HashMap<String, LayerScan> = HashMap::new();
+    let mut archive = tar::Archive::new(File::open(path)?);
+    for entry in archive.entries()? {
+        let entry = entry?;
+        let name = normalize(&entry.path()?.to_string_lossy());
+        if !entries.contains(name.as_str()) {
+            continue;
+        }
+        match scan_layer(entry) {
+            Ok(scan) => {
+                scans.insert(name, scan);
+            }
+            Err(e) => warn!("could not read layer {}: {}", name, e),
+        }
+    }
+
+    let mut reported = HashSet::new();
+    let mut histories = vec![];
+    for layers in &images {
+        for (index, layer) in layers.iter().enumerate() {
+            let Some(scan) = scans.get(&layer.entry) else {
+                continue;
+            };
+            // a layer shared by several images is reported once
+            if !reported.insert(layer.digest.clone()) {
+                continue;
+            }
+            for (path, shell, content) in &scan.histories {
+                let removed_by = layers[index + 1..]
+                    .iter()
+                    .find(|upper| scans.get(&upper.entry).is_some_and(|s| s.removes(path)))
+                    .map(|upper| upper.digest.clone());
+                histories.push(ImageHistory {
+                    layer: layer.digest.clone(),
+                    path: format!("/{path}"),
+                    shell: shell.clone(),
+                    content: content.clone(),
+                    removed_by,
+                });
+            }
+        }
+    }
+    debug!(
+        "found {} history files in image {}",
+        histories.len(),
+        path.display()
+    );
+    Ok(histories)
+}
+
+/// The manifests, indexes and image configs of the tarball, by name
+fn read_metadata(path: &Path) -> Result<HashMap<String, Vec<u8>>> {
+    let mut metadata = HashMap::new();
+    let mut archive = tar::Archive::new(File::open(path)?);
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if !entry.header().entry_type().is_file() || entry.size() > MAX_METADATA_SIZE {
+            continue;
+        }
+        let name = normalize(&entry.path()?.to_string_lossy());
+        if !is_metadata_path(&name) {
+            continue;
+        }
+        // manifests and configs are JSON objects, the other blobs are layers
+        let mut reader = BufReader::new(entry);
+        let is_json = reader.fill_buf()?.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'{');
+        if name.starts_with(BLOBS_FOLDER) && !is_json {
+            continue;
+        }
+        let mut content = vec![];
+        reader.read_to_end(&mut content)?;
+        metadata.insert(name, content);
+    }
+    Ok(metadata)
+}
+
+/// `manifest.json`, `index.json`, the legacy `<id>.json` configs at the root of
+/// a `docker save` tarball and the content addressed blobs
+fn is_metadata_path(name: &str) -> bool {
+    name == DOCKER_MANIFEST
+        || name == OCI_INDEX
+        || (!name.contains('/') && name.ends_with(".json"))
+        || name.starts_with(BLOBS_FOLDER)
+}
+
+/// The layers of every image of the tarball
+fn image_layers(metadata: &HashMap<String, Vec<u8>>) -> Result<Vec<Vec<Layer>>> {
+    if let Some(manifest) = metadata.get(DOCKER_MANIFEST) {
+        let manifests: Vec<DockerManifest> = serde_json::from_slice(manifest)?;
+        return Ok(manifests
+            .iter()
+            .map(|m| docker_layers(m, metadata))
+            .collect());
+    }
+    if let Some(index) = metadata.get(OCI_INDEX) {
+        let index: OciManifest = serde_json::from_slice(index)?;
+        let mut images = vec![];
+        oci_layers(&index, metadata, &mut images)?;
+        return Ok(images);
+    }
+    Err(anyhow!(
+        "{DOCKER_MANIFEST} or {OCI_INDEX} not found, the file is not a `docker save` or OCI \
+         image tarball"
+    ))
+}
+
+/// `docker save` layers are `<id>/layer.tar` or `blobs/sha256/<hex>`. the
+/// digest of a legacy layer is its diff id in the image config
+fn docker_layers(manifest: &DockerManifest, metadata: &HashMap<String, Vec<u8>>) -> Vec<Layer> {
+    let diff_ids = manifest
+        .config
+        .as_ref()
+        .and_then(|config| metadata.get(&normalize(config)))
+        .and_then(|config| serde_json::from_slice::<DockerConfig>(config).ok())
+        .and_then(|config| config.rootfs)
+        .map(|rootfs| rootfs.diff_ids)
+        .unwrap_or_default();
+
+    manifest
+        .layers
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let entry = normalize(entry);
+            let digest = blob_digest(&entry)
+                .or_else(|| diff_ids.get(index).cloned())
+                .unwrap_or_else(|| entry.clone());
+            Layer { entry, digest }
+        })
+        .collect()
+}
+
+/// Follow the OCI index to the image manifests, nested indexes included
+fn oci_layers(
+    manifest: &OciManifest,
+    metadata: &HashMap<String, Vec<u8>>,
+    images: &mut Vec<Vec<Layer>>,
+) -> Result<()> {
+    if !manifest.layers.is_empty() {
+        images.push(
+            manifest
+                .layers
+                .iter()
+                .map(|layer| Layer {
+                    entry: blob_entry(&layer.digest),
+                    digest: layer.digest.clone(),
+                })
+                .collect(),
+        );
+    }
+    for descriptor in &manifest.manifests {
+        let entry = blob_entry(&descriptor.digest);
+        let Some(content) = metadata.get(&entry) else {
+            debug!("manifest {} not found in the tarball", descriptor.digest);
+            continue;
+        };
+        let nested: OciManifest = serde_json::from_slice(content)?;
+        oci_layers(&nested, metadata, images)?;
+    }
+    Ok(())
+}
+
+/// `sha256:<hex>` is stored in `blobs/sha256/<hex>`
+fn blob_entry(digest: &str) -> String {
+    format!("{BLOBS_FOLDER}{}", digest.replacen(':', "/", 1))
+}
+
+fn blob_digest(entry: &str) -> Option<String> {
+    let (algorithm, hex) = entry.strip_prefix(BLOBS_FOLDER)?.split_once('/')?;
+    Some(format!("{algorithm}:{hex}"))
+}
+
+/// Entry path without the leading `./` or `/`
+fn normalize(path: &str) -> String {
+    path.trim_start_matches("./")
+        .trim_start_matches('/')
+        .to_string()
+}
+
+/// Read the history files and the whiteouts of a layer, the layer can be
+/// gzip compressed
+fn scan_layer(reader: impl Read) -> Result<LayerScan> {
+    let mut reader = BufReader::new(reader);
+    let magic = reader.fill_buf()?;
+    if magic.starts_with(ZSTD_MAGIC) {
+        return Err(anyhow!("zstd compressed layers are not supported"));
+    }
+    if magic.starts_with(GZIP_MAGIC) {
+        read_layer(tar::Archive::new(GzDecoder::new(reader)))
+    } else {
+        read_layer(tar::Archive::new(reader))
+    }
+}
+
+fn read_layer(mut archive: tar::Archive<impl Read>) -> Result<LayerScan> {
+    let mut scan = LayerScan::default();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = normalize(&entry.path()?.to_string_lossy());
+        let (dir, file_name) = match path.rsplit_once('/') {
+            Some((dir, file_name)) => (dir.to_string(), file_name.to_string()),
+            None => (String::new(), path.clone()),
+        };
+
+        if file_name == OPAQUE_WHITEOUT {
+            scan.opaque_dirs.push(dir);
+        } else if let Some(deleted) = file_name.strip_prefix(WHITEOUT_PREFIX) {
+            scan.whiteouts.push(if dir.is_empty() {
+                deleted.to_string()
+            } else {
+                format!("{dir}/{deleted}")
+            });
+        } else if let Some((_, shell)) = HISTORY_FILES.iter().find(|(name, _)| *name == file_name) {
+            scan.history_paths.insert(path.clone());
+            if entry.header().entry_type().is_file() {
+                let mut content = vec![];
+                entry.read_to_end(&mut content)?;
+                scan.histories.push((
+                    path,
+                    shell.clone(),
+                    String::from_utf8_lossy(&content).to_string(),
+                ));
+            }
+        }
+    }
+    Ok(scan)
+}
+
+#[cfg(test)]
+mod test_image {
+    use std::io::Write;
+
+    use flate2::{write::GzEncoder, Compression};
+    use insta::assert_debug_snapshot;
+    use tempdir::TempDir;
+
+    use super::*;
+
+    fn append(builder: &mut tar::Builder<impl Write>, path: &str, content: &[u8]) {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o600);
+        header.set_cksum();
+        builder.append_data(&mut header, path, content).unwrap();
+    }
+
+    fn layer(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(vec![]);
+        for (path, content) in files {
+            append(&mut builder, path, content.as_bytes());
+        }
+        builder.into_inner().unwrap()
+    }
+
+    fn gzip(content: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(content).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// a history file, its whiteout in the next layer, and a gzip layer
+    fn layers() -> Vec<Vec<u8>> {
+        vec![
+            layer(&[
+                ("./root/.bash_history", "export GITHUB_TOKEN=ghp_secret\n"),
+                ("./etc/hostname", "image\n"),
+            ]),
+            layer(&[("./root/.wh..bash_history", "")]),
+            gzip(&layer(&[
+                ("./home/app/.ash_history", "ls\n"),
+                ("./home/app/.wh.notes", ""),
+            ])),
+        ]
+    }
+
+    fn write_tarball(path: &Path, entries: &[(String, Vec<u8>)]) {
+        let mut builder = tar::Builder::new(File::create(path).unwrap());
+        for (name, content) in entries {
+            append(&mut builder, name, content);
+        }
+        builder.finish().unwrap();
+    }
+
+    fn summary(histories: &[ImageHistory]) -> Vec<(String, Shell, String)> {
+        histories
+            .iter()
+            .map(|h| (h.title(), h.shell.clone(), h.content.clone()))
+            .collect()
+    }
+
+    /// the layers are not read in the metadata pass
+    fn metadata_names(path: &Path) -> Vec<String> {
+        let mut names = read_metadata(path).unwrap().into_keys().collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn can_scan_docker_save_tarball() {
+        let temp_dir = TempDir::new("image").unwrap();
+        let path = temp_dir.path().join("image.tar");
+        let mut entries = layers()
+            .into_iter()
+            .enumerate()
+            .map(|(i, content)| (format!("layer{i}/layer.tar"), content))
+            .collect::<Vec<_>>();
+        entries.push((
+            "config.json".to_string(),
+            br#"{"rootfs": {"type": "layers", "diff_ids": ["sha256:aaa", "sha256:bbb", "sha256:ccc"]}}"#.to_vec(),
+        ));
+        entries.push((
+            "manifest.json".to_string(),
+            br#"[{"Config": "config.json", "RepoTags": ["app:latest"], "Layers": ["layer0/layer.tar", "layer1/layer.tar", "layer2/layer.tar"]}]"#.to_vec(),
+        ));
+        write_tarball(&path, &entries);
+
+        assert_debug_snapshot!(summary(&scan(&path).unwrap()));
+        assert_debug_snapshot!(metadata_names(&path));
+        temp_dir.close().unwrap();
+    }
+
+    #[test]
+    fn can_scan_oci_layout_tarball() {
+        let temp_dir = TempDir::new("image").unwrap();
+        let path = temp_dir.path().join("image.tar");
+        let mut entries = layers()
+            .into_iter()
+            .enumerate()
+            .map(|(i, content)| (format!("blobs/sha256/layer{i}"), content))
+            .collect::<Vec<_>>();
+        entries.push((
+            "index.json".to_string(),
+            br#"{"schemaVersion": 2, "manifests": [{"digest": "sha256:manifest"}]}"#.to_vec(),
+        ));
+        entries.push((
+            "blobs/sha256/manifest".to_string(),
+            br#"{"schemaVersion": 2, "layers": [{"digest": "sha256:layer0"}, {"digest": "sha256:layer1"}, {"digest": "sha256:layer2"}]}"#.to_vec(),
+        ));
+        write_tarball(&path, &entries);
+
+        assert_debug_snapshot!(summary(&scan(&path).unwrap()));
+        assert_debug_snapshot!(metadata_names(&path));
+        assert_debug_snapshot!(scan(&temp_dir.path().join("missing.tar")).is_err());
+        temp_dir.close().unwrap();
+    }
+}
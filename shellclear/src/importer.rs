@@ -0,0 +1,389 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use regex::Regex;
+use serde_derive::Deserialize;
+use strum::{Display, EnumString};
+
+use crate::data::{Detection, IgnoreRule};
+
+/// Detection rules format of other secret scanners
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum ImportFormat {
+    /// `.gitleaks.toml`
+    Gitleaks,
+    /// trufflehog custom regex detectors YAML
+    Trufflehog,
+}
+
+/// Converted rules, and the rule features that could not be mapped
+#[derive(Debug, Default)]
+pub struct Import {
+    pub patterns: Vec<Detection>,
+    /// allowlists and exclude words, applied to the whole command
+    pub ignores: Vec<IgnoreRule>,
+    pub warnings: Vec<String>,
+}
+
+/// Convert the rules of the given format to shellclear patterns
+///
+/// # Errors
+///
+/// Will return `Err` when the content can't be parsed
+pub fn import(format: ImportFormat, content: &str) -> Result<Import> {
+    match format {
+        ImportFormat::Gitleaks => from_gitleaks(content),
+        ImportFormat::Trufflehog => from_trufflehog(content),
+    }
+}
+
+#[derive(Deserialize)]
+struct GitleaksConfig {
+    #[serde(default)]
+    rules: Vec<GitleaksRule>,
+    allowlist: Option<GitleaksAllowlist>,
+    extend: Option<toml::Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitleaksRule {
+    id: String,
+    description: Option<String>,
+    regex: Option<String>,
+    #[serde(default)]
+    secret_group: u8,
+    entropy: Option<f64>,
+    path: Option<String>,
+    #[serde(default)]
+    keywords: Vec<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    /// gitleaks up to 8.20
+    allowlist: Option<GitleaksAllowlist>,
+    #[serde(default)]
+    allowlists: Vec<GitleaksAllowlist>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitleaksAllowlist {
+    #[serde(default)]
+    regexes: Vec<String>,
+    regex_target: Option<String>,
+    #[serde(default)]
+    stopwords: Vec<String>,
+    #[serde(default)]
+    paths: Vec<String>,
+    #[serde(default)]
+    commits: Vec<String>,
+}
+
+fn from_gitleaks(content: &str) -> Result<Import> {
+    let config: GitleaksConfig = toml::from_str(content)?;
+    let mut import = Import::default();
+    if config.extend.is_some() {
+        import
+            .warnings
+            .push("[extend] is not mapped, import the extended config file too".to_string());
+    }
+    if let Some(allowlist) = &config.allowlist {
+        import_allowlist(&mut import, None, allowlist);
+    }
+
+    for rule in config.rules {
+        let Some(regex) = &rule.regex else {
+            import.warnings.push(format!(
+                "rule {}: skipped, rules without a regex (path only) are not mapped",
+                rule.id
+            ));
+            continue;
+        };
+        let test = match Regex::new(&from_go_regex(regex)) {
+            Ok(r) => r,
+            Err(e) => {
+                import
+                    .warnings
+                    .push(format!("rule {}: skipped, invalid regex. {e}", rule.id));
+                continue;
+            }
+        };
+        if usize::from(rule.secret_group) >= test.captures_len() {
+            import.warnings.push(format!(
+                "rule {}: skipped, secretGroup {} is not a capture group",
+                rule.id, rule.secret_group
+            ));
+            continue;
+        }
+        if rule.path.is_some() {
+            import.warnings.push(format!(
+                "rule {}: path is not mapped, the regex is applied to all commands",
+                rule.id
+            ));
+        }
+
+        for allowlist in rule.allowlist.iter().chain(&rule.allowlists) {
+            import_allowlist(&mut import, Some(&rule.id), allowlist);
+        }
+        import.patterns.push(Detection {
+            description: rule.description.clone(),
+            tags: rule.tags,
+            keywords: rule.keywords,
+            min_entropy: rule.entropy,
+            ..Detection::new(
+                &rule.id,
+                rule.description.as_deref().unwrap_or(&rule.id),
+                test,
+                rule.secret_group,
+            )
+        });
+    }
+    Ok(import)
+}
+
+/// Allowlist regexes and stopwords become ignore rules of the commands that
+/// match them. paths and commits have no meaning in shell history
+fn import_allowlist(import: &mut Import, rule_id: Option<&str>, allowlist: &GitleaksAllowlist) {
+    let name = rule_id.map_or_else(
+        || "global allowlist".to_string(),
+        |id| format!("rule {id} allowlist"),
+    );
+    if !allowlist.regexes.is_empty() && allowlist.regex_target.as_deref() != Some("line") {
+        import.warnings.push(format!(
+            "{name}: regexes are applied to the whole command, not only to the secret"
+        ));
+    }
+    if !allowlist.paths.is_empty() || !allowlist.commits.is_empty() {
+        import
+            .warnings
+            .push(format!("{name}: paths and commits are not mapped"));
+    }
+
+    let regexes = allowlist
+        .regexes
+        .iter()
+        .cloned()
+        .chain(allowlist.stopwords.iter().map(|w| regex::escape(w)));
+    for regex in regexes {
+        match Regex::new(&from_go_regex(&regex)) {
+            Ok(command_regex) => import.ignores.push(IgnoreRule {
+                pattern_id: rule_id.map(ToString::to_string),
+                command_regex: Some(command_regex),
+                reason: Some(format!("imported from gitleaks {name}")),
+                ..IgnoreRule::default()
+            }),
+            Err(e) => import
+                .warnings
+                .push(format!("{name}: regex {regex} skipped, invalid regex. {e}")),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TrufflehogConfig {
+    #[serde(default)]
+    detectors: Vec<TrufflehogDetector>,
+}
+
+#[derive(Deserialize)]
+struct TrufflehogDetector {
+    name: String,
+    #[serde(default)]
+    keywords: Vec<String>,
+    #[serde(default)]
+    regex: BTreeMap<String, String>,
+    entropy: Option<f64>,
+    #[serde(default)]
+    verify: Vec<serde_yaml::Value>,
+    #[serde(default)]
+    exclude_words: Vec<String>,
+    #[serde(default)]
+    exclude_regexes_match: Vec<String>,
+    #[serde(default)]
+    exclude_regexes_capture: Vec<String>,
+}
+
+fn from_trufflehog(content: &str) -> Result<Import> {
+    let config: TrufflehogConfig = serde_yaml::from_str(content)?;
+    let mut import = Import::default();
+
+    for detector in config.detectors {
+        if !detector.verify.is_empty() {
+            import.warnings.push(format!(
+                "detector {}: verify endpoints are not mapped, shellclear works offline",
+                detector.name
+            ));
+        }
+        if detector.regex.len() > 1 {
+            import.warnings.push(format!(
+                "detector {}: each regex is imported as a separate pattern, they don't need to \
+                 match together",
+                detector.name
+            ));
+        }
+        if !detector.exclude_regexes_capture.is_empty() {
+            import.warnings.push(format!(
+                "detector {}: exclude_regexes_capture are applied to the whole command",
+                detector.name
+            ));
+        }
+
+        let mut ids = vec![];
+        for (regex_name, regex) in &detector.regex {
+            let id = to_id(&format!("{}_{}", detector.name, regex_name));
+            let test = match Regex::new(&from_go_regex(regex)) {
+                Ok(r) => r,
+                Err(e) => {
+                    import.warnings.push(format!(
+                        "detector {}: regex {regex_name} skipped, invalid regex. {e}",
+                        detector.name
+                    ));
+                    continue;
+                }
+            };
+            // trufflehog reports the first capture group when there is one
+            let secret_group = u8::from(test.captures_len() > 1);
+            import.patterns.push(Detection {
+                keywords: detector.keywords.clone(),
+                min_entropy: detector.entropy,
+                ..Detection::new(
+                    &id,
+                    &format!("{} {}", detector.name, regex_name),
+                    test,
+                    secret_group,
+                )
+            });
+            ids.push(id);
+        }
+
+        let excludes = detector
+            .exclude_regexes_match
+            .iter()
+            .chain(&detector.exclude_regexes_capture)
+            .cloned()
+            .chain(detector.exclude_words.iter().map(|w| regex::escape(w)));
+        for regex in excludes {
+            let command_regex = match Regex::new(&from_go_regex(&regex)) {
+                Ok(r) => r,
+                Err(e) => {
+                    import.warnings.push(format!(
+                        "detector {}: exclude {regex} skipped, invalid regex. {e}",
+                        detector.name
+                    ));
+                    continue;
+                }
+            };
+            import.ignores.extend(ids.iter().map(|id| IgnoreRule {
+                pattern_id: Some(id.clone()),
+                command_regex: Some(command_regex.clone()),
+                reason: Some(format!(
+                    "imported from trufflehog detector {} excludes",
+                    detector.name
+                )),
+                ..IgnoreRule::default()
+            }));
+        }
+    }
+    Ok(import)
+}
+
+/// Go regexes can escape any punctuation, e.g. `\/`, the regex crate only
+/// allows escaping meta characters
+fn from_go_regex(regex: &str) -> String {
+    let mut result = String::with_capacity(regex.len());
+    let mut chars = regex.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some(next)
+                    if next.is_ascii_punctuation() && !regex_syntax::is_meta_character(next) =>
+                {
+                    result.push(next);
+                }
+                Some(next) => {
+                    result.push(c);
+                    result.push(next);
+                }
+                None => result.push(c),
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// snake case id of a trufflehog detector regex, e.g. `hogtokendetector_hogid`
+fn to_id(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test_importer {
+    use insta::assert_debug_snapshot;
+
+    use super::*;
+
+    #[test]
+    fn can_import_gitleaks() {
+        let content = r#"
+title = "custom rules"
+
+[extend]
+useDefault = true
+
+[allowlist]
+paths = ['''vendor/''']
+regexes = ['''EXAMPLE''']
+
+[[rules]]
+id = "hog-token"
+description = "Hog Token"
+regex = '''hog_token=([a-z0-9]{16})'''
+secretGroup = 1
+entropy = 3.5
+keywords = ["hog_token"]
+tags = ["hog"]
+
+[rules.allowlist]
+stopwords = ["0000000000000000"]
+
+[[rules]]
+id = "path-only"
+path = '''\.pem$'''
+
+[[rules]]
+id = "lookaround"
+regex = '''(?<=token=)\w+'''
+"#;
+        assert_debug_snapshot!(import(ImportFormat::Gitleaks, content).unwrap());
+    }
+
+    #[test]
+    fn can_import_trufflehog() {
+        let content = r#"
+detectors:
+  - name: HogTokenDetector
+    keywords:
+      - hog
+    regex:
+      hogID: '\b(HOG[0-9A-Z]{17})\b'
+      hogToken: '[^A-Za-z0-9+\/]{0,1}([A-Za-z0-9+\/]{40})[^A-Za-z0-9+\/]{0,1}'
+    exclude_words:
+      - EXAMPLE
+    verify:
+      - endpoint: http://localhost:8000/
+        unsafe: true
+"#;
+        assert_debug_snapshot!(import(ImportFormat::Trufflehog, content).unwrap());
+    }
+}
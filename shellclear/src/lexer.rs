@@ -0,0 +1,223 @@
+use std::{ops::Range, path::Path};
+
+/// programs that execute the command given in their arguments
+const WRAPPERS: &[&str] = &["sudo", "doas", "env", "command", "exec", "nohup", "time"];
+/// wrapper options that are followed by a value
+const WRAPPER_VALUE_FLAGS: &[&str] = &["-u", "-g", "-C", "-D", "-h"];
+
+/// Shell word or control operator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    /// word after quote removal
+    pub value: String,
+    /// byte range of the raw word in the command, including quotes
+    pub span: Range<usize>,
+    /// true for control operators like `;`, `|`, `&&` that end a simple command
+    pub operator: bool,
+    /// raw byte offset in the command for every byte of `value`
+    offsets: Vec<usize>,
+}
+
+impl Token {
+    /// Returns the raw byte range in the command of the `value[range]`
+    #[must_use]
+    pub fn raw_span(&self, range: Range<usize>) -> Option<Range<usize>> {
+        if range.is_empty() || range.end > self.offsets.len() {
+            return None;
+        }
+        Some(self.offsets[range.start]..self.offsets[range.end - 1] + 1)
+    }
+}
+
+#[derive(Default)]
+struct Word {
+    value: String,
+    offsets: Vec<usize>,
+    start: Option<usize>,
+}
+
+impl Word {
+    fn push(&mut self, c: char, offset: usize) {
+        self.start.get_or_insert(offset);
+        let mut buf = [0; 4];
+        for (i, _) in c.encode_utf8(&mut buf).bytes().enumerate() {
+            self.offsets.push(offset + i);
+        }
+        self.value.push(c);
+    }
+
+    fn open(&mut self, offset: usize) {
+        self.start.get_or_insert(offset);
+    }
+
+    fn take(&mut self, end: usize) -> Option<Token> {
+        let start = self.start.take()?;
+        Some(Token {
+            value: std::mem::take(&mut self.value),
+            span: start..end,
+            operator: false,
+            offsets: std::mem::take(&mut self.offsets),
+        })
+    }
+}
+
+/// Split a command line into words and control operators following the POSIX
+/// shell quoting rules (single quotes, double quotes, `$'...'` and backslash
+/// escapes).
+#[must_use]
+pub fn tokenize(command: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut word = Word::default();
+    let mut chars = command.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            ' ' | '\t' | '\n' | '\r' => tokens.extend(word.take(i)),
+            '#' if word.start.is_none() => break,
+            ';' | '|' | '&' | '(' | ')' => {
+                tokens.extend(word.take(i));
+                let mut end = i + 1;
+                if let Some(&(_, next)) = chars.peek() {
+                    if (c == '|' || c == '&' || c == ';') && next == c {
+                        chars.next();
+                        end += 1;
+                    }
+                }
+                tokens.push(Token {
+                    value: command[i..end].to_string(),
+                    span: i..end,
+                    operator: true,
+                    offsets: (i..end).collect(),
+                });
+            }
+            '\\' => {
+                if let Some((j, escaped)) = chars.next() {
+                    word.open(i);
+                    if escaped != '\n' {
+                        word.push(escaped, j);
+                    }
+                }
+            }
+            '\'' => {
+                word.open(i);
+                for (j, quoted) in chars.by_ref() {
+                    if quoted == '\'' {
+                        break;
+                    }
+                    word.push(quoted, j);
+                }
+            }
+            '"' => {
+                word.open(i);
+                while let Some((j, quoted)) = chars.next() {
+                    match quoted {
+                        '"' => break,
+                        '\\' => match chars.peek() {
+                            Some(&(k, escaped @ ('"' | '\\' | '$' | '`'))) => {
+                                chars.next();
+                                word.push(escaped, k);
+                            }
+                            _ => word.push(quoted, j),
+                        },
+                        _ => word.push(quoted, j),
+                    }
+                }
+            }
+            '$' if matches!(chars.peek(), Some(&(_, '\''))) => {
+                word.open(i);
+                chars.next();
+                while let Some((j, quoted)) = chars.next() {
+                    match quoted {
+                        '\'' => break,
+                        '\\' => {
+                            if let Some((k, escaped)) = chars.next() {
+                                word.push(escaped, k);
+                            }
+                        }
+                        _ => word.push(quoted, j),
+                    }
+                }
+            }
+            _ => word.push(c, i),
+        }
+    }
+    tokens.extend(word.take(command.len()));
+    tokens
+}
+
+/// Split tokens to simple commands by control operators
+#[must_use]
+pub fn simple_commands(tokens: &[Token]) -> Vec<&[Token]> {
+    tokens
+        .split(|t| t.operator)
+        .filter(|c| !c.is_empty())
+        .collect::<Vec<_>>()
+}
+
+/// Return the basename of the program of a simple command and its arguments,
+/// skipping environment assignments and wrapper programs like `sudo`
+#[must_use]
+pub fn program(tokens: &[Token]) -> Option<(String, &[Token])> {
+    let mut i = 0;
+    while i < tokens.len() {
+        let value = &tokens[i].value;
+        if value.contains('=') && !value.starts_with('-') {
+            i += 1;
+            continue;
+        }
+        let program = Path::new(value)
+            .file_name()
+            .and_then(|f| f.to_str())
+            .unwrap_or(value)
+            .to_string();
+        if !WRAPPERS.contains(&program.as_str()) {
+            return Some((program, &tokens[i + 1..]));
+        }
+        i += 1;
+        while i < tokens.len() && tokens[i].value.starts_with('-') {
+            if WRAPPER_VALUE_FLAGS.contains(&tokens[i].value.as_str()) {
+                i += 1;
+            }
+            i += 1;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test_lexer {
+    use insta::assert_debug_snapshot;
+
+    use super::*;
+
+    #[test]
+    fn can_tokenize() {
+        let command = r#"FOO=1 mysql -u root -p'S3 cret' "db \"x\"" && echo $'a\'b' \; # comment"#;
+        let tokens = tokenize(command)
+            .iter()
+            .map(|t| (t.value.clone(), &command[t.span.clone()], t.operator))
+            .collect::<Vec<_>>();
+
+        assert_debug_snapshot!(tokens);
+    }
+
+    #[test]
+    fn can_get_raw_span() {
+        let command = r#"mysql -p'S3 cret'"#;
+        let tokens = tokenize(command);
+        let span = tokens[1].raw_span(2..tokens[1].value.len()).unwrap();
+
+        assert_eq!(&command[span], "S3 cret");
+    }
+
+    #[test]
+    fn can_split_simple_commands() {
+        let tokens = tokenize("ls -la | grep foo; echo done");
+        let commands = simple_commands(&tokens)
+            .iter()
+            .map(|c| c.iter().map(|t| t.value.clone()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        assert_debug_snapshot!(commands);
+    }
+}
@@ -2,17 +2,31 @@ extern crate core;
 
 pub use self::{
     emoji::Emojis,
-    state::{init, ShellContext},
+    state::{group_by_user, init, init_all_users, resolve_home, resolve_in_root, ShellContext},
 };
 
+mod correlator;
 mod emoji;
+mod lexer;
 mod masker;
+mod placeholder;
 mod state;
 
 pub mod clearer;
 pub mod config;
 pub mod data;
+pub mod detector;
 pub mod dialog;
 pub mod engine;
 pub mod exporter;
+pub mod fingerprint;
+pub mod git;
+pub mod image;
+pub mod importer;
+pub mod linter;
+pub mod packs;
+pub mod registry;
+pub mod settings;
 pub mod shell;
+pub mod tester;
+pub mod validator;
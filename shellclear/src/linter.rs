@@ -0,0 +1,322 @@
+use std::{
+    collections::HashSet,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use regex::{Regex, RegexBuilder};
+use regex_syntax::hir::{Hir, HirKind, RepetitionKind, RepetitionRange};
+use serde_derive::Deserialize;
+
+use crate::{config::Config, data::Detection, packs::Packs, registry::PatternRegistry};
+
+/// compiled size of a pattern that is reported, every pattern runs on every
+/// command
+const MAX_COMPILED_SIZE: usize = 1024 * 1024;
+/// counted repetitions above this count are reported, e.g. `\w{1000,}`
+const MAX_REPETITION: u32 = 256;
+
+/// A problem in a custom patterns file
+#[derive(Debug)]
+pub struct PatternIssue {
+    pub path: PathBuf,
+    /// line and column of a YAML error
+    pub location: Option<(usize, usize)>,
+    /// pattern id, or its position in the file when the id is empty
+    pub pattern: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for PatternIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        if let Some((line, column)) = self.location {
+            write!(f, ":{line}:{column}")?;
+        }
+        if let Some(pattern) = &self.pattern {
+            write!(f, ": pattern {pattern}")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// The fields of a pattern that are checked before the pattern is
+/// deserialized, so an invalid regex is reported with the pattern id
+#[derive(Deserialize)]
+struct RawPattern {
+    #[serde(default)]
+    id: String,
+    test: String,
+    secret_group: u8,
+}
+
+/// Lint all the existing system, user and project pattern files and the
+/// enabled packs
+#[must_use]
+pub fn lint_config(config: &Config) -> Vec<PatternIssue> {
+    let mut issues = PatternRegistry::custom_sources(config)
+        .iter()
+        .flat_map(|(_, path)| lint_file(path))
+        .collect::<Vec<_>>();
+
+    issues.extend(lint_packs(&Packs::with_config(config)));
+    issues
+}
+
+/// Lint the load errors and the patterns of the enabled packs
+#[must_use]
+pub fn lint_packs(packs: &Packs) -> Vec<PatternIssue> {
+    let mut issues = packs
+        .errors
+        .iter()
+        .map(|(path, e)| PatternIssue {
+            path: path.clone(),
+            location: None,
+            pattern: None,
+            message: e.clone(),
+        })
+        .collect::<Vec<_>>();
+    for pack in packs.enabled() {
+        issues.extend(lint_entries(
+            &pack.path,
+            pack.patterns
+                .iter()
+                .map(|p| (p.id.as_str(), p.test.as_str(), p.secret_group)),
+        ));
+    }
+    issues
+}
+
+/// Lint a patterns file
+#[must_use]
+pub fn lint_file(path: &Path) -> Vec<PatternIssue> {
+    match fs::read_to_string(path) {
+        Ok(content) => lint_yaml(path, &content),
+        Err(e) => vec![PatternIssue {
+            path: path.to_path_buf(),
+            location: None,
+            pattern: None,
+            message: e.to_string(),
+        }],
+    }
+}
+
+/// Lint the content of a patterns file
+#[must_use]
+pub fn lint_yaml(path: &Path, content: &str) -> Vec<PatternIssue> {
+    let raw_patterns: Vec<RawPattern> = match serde_yaml::from_str(content) {
+        Ok(p) => p,
+        Err(e) => return vec![yaml_issue(path, &e)],
+    };
+
+    let mut issues = lint_entries(
+        path,
+        raw_patterns
+            .iter()
+            .map(|p| (p.id.as_str(), p.test.as_str(), p.secret_group)),
+    );
+
+    // the remaining fields, e.g. an unknown severity
+    if issues.is_empty() {
+        if let Err(e) = serde_yaml::from_str::<Vec<Detection>>(content) {
+            issues.push(yaml_issue(path, &e));
+        }
+    }
+    issues
+}
+
+/// Check the ids, the regex and the secret group of the id, regex and secret
+/// group entries
+fn lint_entries<'a>(
+    path: &Path,
+    entries: impl Iterator<Item = (&'a str, &'a str, u8)>,
+) -> Vec<PatternIssue> {
+    let mut issues = vec![];
+    let mut ids = HashSet::new();
+    for (index, (id, test, secret_group)) in entries.enumerate() {
+        let pattern = if id.is_empty() {
+            format!("#{}", index + 1)
+        } else {
+            id.to_string()
+        };
+        let mut issue = |message: String| {
+            issues.push(PatternIssue {
+                path: path.to_path_buf(),
+                location: None,
+                pattern: Some(pattern.clone()),
+                message,
+            });
+        };
+
+        if id.trim().is_empty() {
+            issue("id is empty".to_string());
+        } else if !ids.insert(id) {
+            issue("id is not unique".to_string());
+        }
+        for message in lint_regex(test, secret_group) {
+            issue(message);
+        }
+    }
+    issues
+}
+
+/// Check that the regex compiles, has the secret group, stays small and
+/// doesn't match every command. the regex engine matches in linear time, so
+/// the size of the compiled regex is what makes a pattern slow
+fn lint_regex(test: &str, secret_group: u8) -> Vec<String> {
+    let regex = match Regex::new(test) {
+        Ok(r) => r,
+        Err(e) => return vec![format!("invalid regex. {e}")],
+    };
+
+    let mut messages = vec![];
+    let groups = regex.captures_len() - 1;
+    if usize::from(secret_group) > groups {
+        messages.push(format!(
+            "secret_group {secret_group} is not a capture group, the regex has {groups} groups"
+        ));
+    }
+    if regex.is_match("") {
+        messages.push("regex matches the empty string".to_string());
+    }
+    if let Ok(hir) = regex_syntax::Parser::new().parse(test) {
+        let count = max_repetition(&hir);
+        if count > MAX_REPETITION {
+            messages.push(format!(
+                "regex has a counted repetition of {count}, above {MAX_REPETITION}, which makes \
+                 the compiled regex large and slow"
+            ));
+        }
+    }
+    if let Err(regex::Error::CompiledTooBig(_)) = RegexBuilder::new(test)
+        .size_limit(MAX_COMPILED_SIZE)
+        .build()
+    {
+        messages.push(format!(
+            "regex compiles to more than {} KiB, which slows down every scan. use smaller \
+             counted repetitions or ASCII classes, e.g. (?-u:\\w)",
+            MAX_COMPILED_SIZE / 1024
+        ));
+    }
+    messages
+}
+
+/// The largest count of the counted repetitions, e.g. 1000 for `a{2,1000}`
+fn max_repetition(hir: &Hir) -> u32 {
+    match hir.kind() {
+        HirKind::Repetition(rep) => {
+            let count = match &rep.kind {
+                RepetitionKind::Range(
+                    RepetitionRange::Exactly(n) | RepetitionRange::AtLeast(n),
+                ) => *n,
+                RepetitionKind::Range(RepetitionRange::Bounded(_, n)) => *n,
+                _ => 0,
+            };
+            count.max(max_repetition(&rep.hir))
+        }
+        HirKind::Group(group) => max_repetition(&group.hir),
+        HirKind::Concat(hirs) | HirKind::Alternation(hirs) => {
+            hirs.iter().map(max_repetition).max().unwrap_or(0)
+        }
+        _ => 0,
+    }
+}
+
+fn yaml_issue(path: &Path, e: &serde_yaml::Error) -> PatternIssue {
+    let message = e.to_string();
+    PatternIssue {
+        path: path.to_path_buf(),
+        location: e.location().map(|l| (l.line(), l.column())),
+        // the location is already part of the issue
+        message: message
+            .split(" at line ")
+            .next()
+            .unwrap_or(&message)
+            .to_string(),
+        pattern: None,
+    }
+}
+
+#[cfg(test)]
+mod test_linter {
+    use insta::assert_debug_snapshot;
+
+    use super::*;
+    use crate::engine::SENSITIVE_COMMANDS;
+
+    #[test]
+    fn built_in_patterns_are_valid() {
+        assert_debug_snapshot!(lint_yaml(Path::new("built-in"), SENSITIVE_COMMANDS));
+    }
+
+    #[test]
+    fn can_lint_patterns() {
+        let content = r###"
+- id: ok
+  name: ok
+  test: token=(\w+)
+  secret_group: 1
+- id: ok
+  name: duplicate
+  test: secret=(\w+)
+  secret_group: 1
+- name: no id
+  test: key=(\w+)
+  secret_group: 0
+- id: bad_group
+  name: bad group
+  test: token=\w+
+  secret_group: 1
+- id: bad_regex
+  name: bad regex
+  test: token=(\w+
+  secret_group: 1
+- id: empty_match
+  name: empty match
+  test: (\w*)
+  secret_group: 1
+- id: nested
+  name: nested is linear time
+  test: token=((\w+)+)
+  secret_group: 1
+- id: large_repetition
+  name: large repetition
+  test: token=((?-u:\w){1000,})
+  secret_group: 1
+- id: too_big
+  name: too big
+  test: token=(\w{40})
+  secret_group: 1
+"###;
+        let issues = lint_yaml(Path::new("patterns.yaml"), content)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_debug_snapshot!(issues);
+    }
+
+    #[test]
+    fn can_lint_yaml_errors() {
+        let missing_field = r###"
+- id: ok
+  name: ok
+  test: token=(\w+)
+- id: other
+"###;
+        let bad_severity = r###"
+- id: ok
+  name: ok
+  test: token=(\w+)
+  secret_group: 1
+  severity: urgent
+"###;
+        assert_debug_snapshot!(lint_yaml(Path::new("patterns.yaml"), missing_field)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>());
+        assert_debug_snapshot!(lint_yaml(Path::new("patterns.yaml"), bad_severity)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>());
+    }
+}
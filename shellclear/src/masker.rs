@@ -1,10 +1,6 @@
 use mask_text::Kind;
 
-use crate::data::Command;
-
-const DEFAULT_PERCENTAGE: u8 = 80;
-const DEFAULT_MIN_CHARS: usize = 3;
-const DEFAULT_MASK_CHARS: &str = "*";
+use crate::{data::Command, settings::MaskSettings};
 
 pub struct Masker {
     percentage: u8,
@@ -14,10 +10,14 @@ pub struct Masker {
 
 impl Masker {
     pub fn new() -> Self {
+        Self::with_settings(&MaskSettings::default())
+    }
+
+    pub fn with_settings(settings: &MaskSettings) -> Self {
         Self {
-            percentage: DEFAULT_PERCENTAGE,
-            min_chars: DEFAULT_MIN_CHARS,
-            mask_chars: DEFAULT_MASK_CHARS.to_string(),
+            percentage: settings.percentage,
+            min_chars: settings.min_chars,
+            mask_chars: settings.chars.clone(),
         }
     }
 
@@ -26,13 +26,7 @@ impl Masker {
 
         for command in commands_with_detections {
             let should_mask = command.secrets.iter().any(|secret| {
-                let replaced_secret = Kind::Percentage(
-                    secret.clone(),
-                    self.percentage,
-                    self.min_chars,
-                    self.mask_chars.clone(),
-                )
-                .mask();
+                let replaced_secret = self.mask(secret);
 
                 // there can still be an false-positive here, if the text is all made up of
                 // asterisks
@@ -46,15 +40,41 @@ impl Masker {
                 continue;
             }
 
+            // mask secrets with a known position in place, so only that argument is masked
+            let mut spans = command.spans.clone();
+            spans.sort_by_key(|s| std::cmp::Reverse(s.start));
+            let mut span_secrets = vec![];
+            let mut last_start = usize::MAX;
+            for span in spans {
+                let Some(secret) = command.command.get(span.clone()).map(str::to_string) else {
+                    continue;
+                };
+                if span.end > last_start {
+                    continue;
+                }
+                last_start = span.start;
+                let replaced_secret = self.mask(&secret);
+
+                // data ends with the command for line based history files
+                if command.data.ends_with(&command.command) {
+                    let offset = command.data.len() - command.command.len();
+                    command
+                        .data
+                        .replace_range(offset + span.start..offset + span.end, &replaced_secret);
+                } else {
+                    command.data = command.data.replacen(&secret, &replaced_secret, 1);
+                }
+                command.command.replace_range(span, &replaced_secret);
+                span_secrets.push(secret);
+            }
+
             // Can be optimized, we can chose to not re-mask the secret, using a map, etc...
-            for secret in &command.secrets {
-                let replaced_secret = Kind::Percentage(
-                    secret.clone(),
-                    self.percentage,
-                    self.min_chars,
-                    self.mask_chars.clone(),
-                )
-                .mask();
+            for secret in command
+                .secrets
+                .iter()
+                .filter(|secret| !span_secrets.contains(secret))
+            {
+                let replaced_secret = self.mask(secret);
 
                 command.command = command.command.replace(secret, &replaced_secret);
                 command.data = command.data.replace(secret, &replaced_secret);
@@ -62,6 +82,16 @@ impl Masker {
         }
     }
 
+    fn mask(&self, secret: &str) -> String {
+        Kind::Percentage(
+            secret.to_string(),
+            self.percentage,
+            self.min_chars,
+            self.mask_chars.clone(),
+        )
+        .mask()
+    }
+
     fn get_commands_with_detection(commands: &mut [Command]) -> Vec<&mut Command> {
         commands
             .iter_mut()
@@ -72,12 +102,14 @@ impl Masker {
 
 #[cfg(test)]
 mod test_masker {
+    use std::ops::Range;
+
     use anyhow::Result;
     use insta::assert_debug_snapshot;
     use regex::Regex;
 
     use crate::{
-        data::{Command, Detection},
+        data::{Command, Detection, FormatCheck},
         masker::Masker,
         shell::Shell::Zshrc,
     };
@@ -86,15 +118,20 @@ mod test_masker {
     fn mask_results() -> Result<()> {
         let mut commands = vec![Command {
             shell_type: Zshrc,
-            detections: vec![Detection {
-                test: Regex::new("export (MASK_ME)")?,
-                name: "mask me mock".to_string(),
-                secret_group: 1,
-                id: "".to_string(),
-            }],
+            detections: vec![Detection::new(
+                "",
+                "mask me mock",
+                Regex::new("export (MASK_ME)")?,
+                1,
+            )],
             command: "export MASK_ME".to_string(),
             data: "export MASK_ME".to_string(),
             secrets: vec!["MASK_ME".to_string()],
+            spans: vec![],
+            jwt_claims: vec![],
+            format_check: FormatCheck::Unchecked,
+            incident: None,
+            fingerprints: vec![],
         }];
 
         Masker::new().mask_sensitive_findings(commands.as_mut());
@@ -108,15 +145,42 @@ mod test_masker {
     fn remove_already_masked_detection() -> Result<()> {
         let mut commands = [Command {
             shell_type: Zshrc,
-            detections: vec![Detection {
-                test: Regex::new("export (MASK_ME)")?,
-                name: "mask me mock".to_string(),
-                secret_group: 1,
-                id: "".to_string(),
-            }],
+            detections: vec![Detection::new(
+                "",
+                "mask me mock",
+                Regex::new("export (MASK_ME)")?,
+                1,
+            )],
             command: "export MA*****".to_string(),
             data: "export MA*****".to_string(),
             secrets: vec!["MASK_ME".to_string()],
+            spans: vec![],
+            jwt_claims: vec![],
+            format_check: FormatCheck::Unchecked,
+            incident: None,
+            fingerprints: vec![],
+        }];
+
+        Masker::new().mask_sensitive_findings(commands.as_mut());
+
+        assert_debug_snapshot!(commands);
+
+        Ok(())
+    }
+
+    #[test]
+    fn mask_only_secret_spans() -> Result<()> {
+        let mut commands = vec![Command {
+            shell_type: Zshrc,
+            detections: vec![Detection::new("", "mask me mock", Regex::new("mysql")?, 0)],
+            command: "mysql -proot -u root".to_string(),
+            data: ": 1655110559:0;mysql -proot -u root".to_string(),
+            secrets: vec!["root".to_string()],
+            spans: vec![Range { start: 8, end: 12 }],
+            jwt_claims: vec![],
+            format_check: FormatCheck::Unchecked,
+            incident: None,
+            fingerprints: vec![],
         }];
 
         Masker::new().mask_sensitive_findings(commands.as_mut());
@@ -0,0 +1,298 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use log::debug;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{config::Config, data::Detection};
+
+/// Pack directories and the packs to skip, `packs.yaml` in the config folder
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PacksConfig {
+    /// extra directories of packs, e.g. a checked out team repository.
+    /// relative paths are relative to the config folder
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<PathBuf>,
+    /// names of the packs to skip
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled: Vec<String>,
+}
+
+/// A pack file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PackFile {
+    name: String,
+    version: String,
+    /// names of other packs, or paths relative to the pack file
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    patterns: Vec<Detection>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PatternPack {
+    pub name: String,
+    pub version: String,
+    pub path: PathBuf,
+    pub include: Vec<String>,
+    pub patterns: Vec<Detection>,
+    pub enabled: bool,
+}
+
+impl PatternPack {
+    /// Load a pack file
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` file not found or yaml is invalid
+    pub fn load(path: &Path) -> Result<Self> {
+        let f = fs::File::open(path)?;
+        let pack: PackFile = serde_yaml::from_reader(f)?;
+        Ok(Self {
+            name: pack.name,
+            version: pack.version,
+            path: path.to_path_buf(),
+            include: pack.include,
+            patterns: pack.patterns,
+            enabled: true,
+        })
+    }
+
+    /// `name@version`
+    #[must_use]
+    pub fn label(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+}
+
+/// The packs in merge order, included packs come before the packs that
+/// include them so a pack can override the patterns of its includes
+#[derive(Debug, Default)]
+pub struct Packs {
+    pub packs: Vec<PatternPack>,
+    /// file and error of the packs that could not be loaded
+    pub errors: Vec<(PathBuf, String)>,
+}
+
+impl Packs {
+    /// Load the packs of `patterns.d` in the config folder and of the extra
+    /// pack directories, sorted by name
+    #[must_use]
+    pub fn with_config(config: &Config) -> Self {
+        let mut packs = Self::default();
+        let packs_config = config.get_packs_config().unwrap_or_else(|e| {
+            packs
+                .errors
+                .push((config.packs_config_path.clone(), e.to_string()));
+            PacksConfig::default()
+        });
+
+        let mut dirs = vec![config.pattern_packs_path.clone()];
+        dirs.extend(packs_config.paths.iter().map(|p| config.app_path.join(p)));
+        let mut discovered: BTreeMap<String, PatternPack> = BTreeMap::new();
+        for dir in dirs {
+            for path in pack_files(&dir) {
+                match PatternPack::load(&path) {
+                    Ok(pack) => {
+                        if let Some(existing) = discovered.get(&pack.name) {
+                            packs.errors.push((
+                                path,
+                                format!(
+                                    "pack {} is already loaded from {}",
+                                    pack.name,
+                                    existing.path.display()
+                                ),
+                            ));
+                        } else {
+                            discovered.insert(pack.name.clone(), pack);
+                        }
+                    }
+                    Err(e) => packs.errors.push((path, e.to_string())),
+                }
+            }
+        }
+
+        for pack in discovered.values() {
+            packs.visit(
+                pack.clone(),
+                &discovered,
+                &packs_config.disabled,
+                &mut vec![],
+            );
+        }
+        packs
+    }
+
+    /// Add the includes of the pack and then the pack itself. the includes of
+    /// a disabled pack are not loaded
+    fn visit(
+        &mut self,
+        mut pack: PatternPack,
+        discovered: &BTreeMap<String, PatternPack>,
+        disabled: &[String],
+        stack: &mut Vec<String>,
+    ) {
+        if stack.contains(&pack.name) {
+            self.errors.push((
+                pack.path.clone(),
+                format!("include cycle {} -> {}", stack.join(" -> "), pack.name),
+            ));
+            return;
+        }
+        if self.packs.iter().any(|p| p.name == pack.name) {
+            return;
+        }
+
+        pack.enabled = !disabled.contains(&pack.name);
+        if pack.enabled {
+            stack.push(pack.name.clone());
+            for include in &pack.include {
+                match resolve_include(&pack, include, discovered) {
+                    Ok(included) => self.visit(included, discovered, disabled, stack),
+                    Err(e) => self
+                        .errors
+                        .push((pack.path.clone(), format!("include {include}: {e}"))),
+                }
+            }
+            stack.pop();
+        } else {
+            debug!("pack {} is disabled", pack.label());
+        }
+        self.packs.push(pack);
+    }
+
+    /// The packs that are not disabled
+    pub fn enabled(&self) -> impl Iterator<Item = &PatternPack> {
+        self.packs.iter().filter(|p| p.enabled)
+    }
+}
+
+/// The YAML files of the directory, sorted by file name
+fn pack_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        debug!("packs directory not found: {}", dir.display());
+        return vec![];
+    };
+    let mut files = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.is_file()
+                && p.extension()
+                    .is_some_and(|ext| ext == "yaml" || ext == "yml")
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+    files
+}
+
+/// An include is the name of a discovered pack or a path relative to the pack
+fn resolve_include(
+    pack: &PatternPack,
+    include: &str,
+    discovered: &BTreeMap<String, PatternPack>,
+) -> Result<PatternPack> {
+    if let Some(included) = discovered.get(include) {
+        return Ok(included.clone());
+    }
+    let path = pack
+        .path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(include);
+    if path.is_file() {
+        PatternPack::load(&path)
+    } else {
+        Err(anyhow!("pack not found"))
+    }
+}
+
+#[cfg(test)]
+mod test_packs {
+    use insta::assert_debug_snapshot;
+    use tempdir::TempDir;
+
+    use super::*;
+
+    fn write_pack(dir: &Path, file: &str, content: &str) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join(file), content).unwrap();
+    }
+
+    #[test]
+    fn can_load_packs_with_includes() {
+        let temp_dir = TempDir::new("packs").unwrap();
+        let config = Config::with_custom_path(temp_dir.path());
+        let team_path = temp_dir.path().join("team-repo");
+        write_pack(
+            &config.pattern_packs_path,
+            "cloud.yaml",
+            r###"
+name: cloud
+version: 1.0.0
+include: [base, ../../shared/extra.yaml]
+patterns:
+  - id: cloud_token
+    name: Cloud Token
+    test: CLOUD_TOKEN=(\w+)
+    secret_group: 1
+"###,
+        );
+        write_pack(
+            &config.pattern_packs_path,
+            "legacy.yaml",
+            r###"
+name: legacy
+version: 0.1.0
+include: [not-found]
+"###,
+        );
+        write_pack(
+            &team_path,
+            "base.yaml",
+            r###"
+name: base
+version: 2.0.0
+include: [cloud]
+patterns:
+  - id: base_token
+    name: Base Token
+    test: BASE_TOKEN=(\w+)
+    secret_group: 1
+"###,
+        );
+        write_pack(
+            &temp_dir.path().join("shared"),
+            "extra.yaml",
+            r###"
+name: extra
+version: 1.0.0
+"###,
+        );
+        config
+            .save_packs_config(&PacksConfig {
+                paths: vec![team_path],
+                disabled: vec!["legacy".to_string()],
+            })
+            .unwrap();
+
+        let packs = Packs::with_config(&config);
+        assert_debug_snapshot!(packs
+            .packs
+            .iter()
+            .map(|p| (p.label(), p.enabled, p.patterns.len()))
+            .collect::<Vec<_>>());
+        assert_debug_snapshot!(packs
+            .errors
+            .iter()
+            .map(|(_, e)| e.clone())
+            .collect::<Vec<_>>());
+        temp_dir.close().unwrap();
+    }
+}
@@ -0,0 +1,103 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    /// variable expansions and command substitutions, e.g. `$TOKEN`, `${TOKEN}`,
+    /// `$(pass show gh)`, `` `cat token` ``, `%TOKEN%` or `$env:TOKEN`
+    static ref VARIABLE_REFERENCE: Regex =
+        Regex::new(r"^(\$\{?[A-Za-z_][A-Za-z0-9_]*\}?|\$\(|`|%[A-Za-z_][A-Za-z0-9_]*%|\$env:)")
+            .unwrap();
+    /// values that are obviously not real secrets, e.g. `<your-token>`
+    static ref PLACEHOLDER: Regex = Regex::new(
+        r"(?i)^(<[^>]*>|\[[^\]]*\]|\{\{.*\}\}|x{3,}|\.{3}|(your|my)([-_][a-z]+)*[-_](password|passwd|token|secret|api[-_]?key|key)|change[-_]?me|placeholder|redacted|todo|example|dummy|null|none)$"
+    )
+    .unwrap();
+}
+
+const QUOTES: &[char] = &['"', '\''];
+
+/// Returns true when the secret captured in `command` between `start` and
+/// `end` is empty, a variable reference/command substitution or an obvious
+/// placeholder. a single quoted value is not expanded by the shell, so
+/// `'$ecret'` is a secret
+#[must_use]
+pub fn is_placeholder(command: &str, start: usize, end: usize) -> bool {
+    let raw = &command[start..end];
+    let trimmed = raw.trim();
+    let value = trimmed.trim_matches(QUOTES);
+    if value.is_empty() {
+        return true;
+    }
+
+    let offset = start
+        + (raw.len() - raw.trim_start().len())
+        + (trimmed.len() - trimmed.trim_start_matches(QUOTES).len());
+    (!is_single_quoted(command, offset) && VARIABLE_REFERENCE.is_match(value))
+        || PLACEHOLDER.is_match(value)
+}
+
+/// Is the byte position of the command inside single quotes, where the shell
+/// doesn't expand variables
+fn is_single_quoted(command: &str, position: usize) -> bool {
+    let mut single = false;
+    let mut double = false;
+    let mut escaped = false;
+    for c in command[..position].chars() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if !single => escaped = true,
+            '\'' if !double => single = !single,
+            '"' if !single => double = !double,
+            _ => {}
+        }
+    }
+    single
+}
+
+#[cfg(test)]
+mod test_placeholder {
+    use insta::assert_debug_snapshot;
+    use regex::Regex;
+
+    use super::*;
+
+    #[test]
+    fn can_detect_placeholders() {
+        let pattern = Regex::new(r"TOKEN=([^ ]{0,100})").unwrap();
+        let commands = vec![
+            "export TOKEN=$(pass show gh)",
+            "export TOKEN=${GH_TOKEN}",
+            "export TOKEN=$GH_TOKEN",
+            "export TOKEN=`cat ~/.token`",
+            "export TOKEN=<your-token>",
+            "export TOKEN=\"<your-token>\"",
+            "export TOKEN=your_token",
+            "export TOKEN=xxxxxxxx",
+            "export TOKEN=",
+            "export TOKEN=''",
+            "export TOKEN=ghp_secret",
+            "export TOKEN=\"s3cr3t$(date)\"",
+            "export TOKEN=your-api-key",
+            "export TOKEN=my_github_token",
+            "export TOKEN=mysecretpassword",
+            "export TOKEN=MyDogRex",
+            "export TOKEN='$ecret'",
+            "export TOKEN=\"$GH_TOKEN\"",
+            "echo 'export TOKEN=$GH_TOKEN'",
+            "echo \"it's\" && export TOKEN=$GH_TOKEN",
+        ];
+
+        let results = commands
+            .iter()
+            .map(|command| {
+                let m = pattern.captures(command).unwrap().get(1).unwrap();
+                (*command, is_placeholder(command, m.start(), m.end()))
+            })
+            .collect::<Vec<_>>();
+
+        assert_debug_snapshot!(results);
+    }
+}
@@ -0,0 +1,257 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use log::debug;
+use strum::Display;
+
+use crate::{
+    config::Config,
+    data::Detection,
+    engine::SENSITIVE_COMMANDS,
+    packs::{Packs, PatternPack},
+};
+
+/// Where a pattern is defined. later sources override earlier ones by id
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Display)]
+#[strum(serialize_all = "kebab-case")]
+pub enum PatternSource {
+    /// embedded `sensitive-patterns.yaml`
+    BuiltIn,
+    /// `/etc/shellclear/sensitive-patterns.yaml`
+    System,
+    /// packs of `patterns.d` in the config folder and of the extra pack
+    /// directories
+    Pack,
+    /// `sensitive-patterns.yaml` in the config folder
+    User,
+    /// `.shellclear/sensitive-patterns.yaml` in the current directory or its
+    /// parents
+    Project,
+}
+
+#[derive(Debug, Clone)]
+pub struct RegisteredPattern {
+    pub detection: Detection,
+    pub source: PatternSource,
+    /// file the pattern was loaded from, `None` for built-in patterns
+    pub path: Option<PathBuf>,
+    /// source of the pattern with the same id that this pattern replaced
+    pub overrides: Option<PatternSource>,
+    /// `name@version` of the pack the pattern came from
+    pub pack: Option<String>,
+}
+
+/// Sensitive patterns of all the sources, a pattern replaces the pattern with
+/// the same id from a source with lower precedence
+#[derive(Debug, Default)]
+pub struct PatternRegistry {
+    patterns: Vec<RegisteredPattern>,
+    /// patterns that were replaced by a pattern with the same id
+    overridden: Vec<RegisteredPattern>,
+}
+
+impl PatternRegistry {
+    /// Registry with the built-in patterns only
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` when the built-in patterns are invalid
+    pub fn with_built_in() -> Result<Self> {
+        let mut registry = Self::default();
+        registry.add(
+            PatternSource::BuiltIn,
+            None,
+            serde_yaml::from_str(SENSITIVE_COMMANDS)?,
+        );
+        Ok(registry)
+    }
+
+    /// Load the built-in, system, packs, user and project patterns. invalid or
+    /// missing files are skipped
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` when the built-in patterns are invalid
+    pub fn with_config(config: &Config) -> Result<Self> {
+        let mut registry = Self::with_built_in()?;
+        let (system, others): (Vec<_>, Vec<_>) = Self::custom_sources(config)
+            .into_iter()
+            .partition(|(source, _)| *source < PatternSource::Pack);
+        for (source, path) in system {
+            registry.add_file(source, &path);
+        }
+        let packs = Packs::with_config(config);
+        for (path, e) in &packs.errors {
+            debug!("could not load pack {}. {}", path.display(), e);
+        }
+        for pack in packs.enabled() {
+            registry.add_pack(pack);
+        }
+        for (source, path) in others {
+            registry.add_file(source, &path);
+        }
+        Ok(registry)
+    }
+
+    /// The existing custom pattern files, in precedence order
+    #[must_use]
+    pub fn custom_sources(config: &Config) -> Vec<(PatternSource, PathBuf)> {
+        [
+            (
+                PatternSource::System,
+                Some(config.system_patterns_path.clone()),
+            ),
+            (
+                PatternSource::User,
+                Some(config.sensitive_commands_path.clone()),
+            ),
+            (PatternSource::Project, config.project_patterns_path.clone()),
+        ]
+        .into_iter()
+        .filter_map(|(source, path)| Some((source, path.filter(|p| p.exists())?)))
+        .collect()
+    }
+
+    fn add_file(&mut self, source: PatternSource, path: &Path) {
+        match Config::load_patterns(path) {
+            Ok(patterns) => self.add(source, Some(path), patterns),
+            Err(e) => debug!("could not load {} patterns. {:?}", source, e),
+        }
+    }
+
+    /// Add the patterns of the pack
+    pub fn add_pack(&mut self, pack: &PatternPack) {
+        debug!(
+            "loading {} patterns of pack {} from {}",
+            pack.patterns.len(),
+            pack.label(),
+            pack.path.display()
+        );
+        self.insert(
+            PatternSource::Pack,
+            Some(&pack.path),
+            Some(&pack.label()),
+            pack.patterns.clone(),
+        );
+    }
+
+    /// Add patterns of the given source, replacing the patterns with the same
+    /// id. patterns without id are always added
+    pub fn add(&mut self, source: PatternSource, path: Option<&Path>, patterns: Vec<Detection>) {
+        self.insert(source, path, None, patterns);
+    }
+
+    fn insert(
+        &mut self,
+        source: PatternSource,
+        path: Option<&Path>,
+        pack: Option<&str>,
+        patterns: Vec<Detection>,
+    ) {
+        for detection in patterns {
+            let existing = self
+                .patterns
+                .iter()
+                .position(|p| !detection.id.is_empty() && p.detection.id == detection.id);
+            let pattern = RegisteredPattern {
+                overrides: existing.map(|i| self.patterns[i].source),
+                detection,
+                source,
+                path: path.map(Path::to_path_buf),
+                pack: pack.map(ToString::to_string),
+            };
+            if let Some(pack) = pack {
+                debug!("pattern {} loaded from pack {}", pattern.detection.id, pack);
+            }
+            match existing {
+                Some(i) => {
+                    debug!(
+                        "pattern {} from {} overrides {}",
+                        pattern.detection.id, source, self.patterns[i].source
+                    );
+                    let replaced = std::mem::replace(&mut self.patterns[i], pattern);
+                    self.overridden.push(replaced);
+                }
+                None => self.patterns.push(pattern),
+            }
+        }
+    }
+
+    /// The effective patterns, in the order they were first registered
+    #[must_use]
+    pub fn patterns(&self) -> &[RegisteredPattern] {
+        &self.patterns
+    }
+
+    /// Patterns that were replaced by a source with higher precedence
+    #[must_use]
+    pub fn overridden(&self) -> &[RegisteredPattern] {
+        &self.overridden
+    }
+
+    #[must_use]
+    pub fn detections(&self) -> Vec<Detection> {
+        self.patterns.iter().map(|p| p.detection.clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod test_registry {
+    use std::fs;
+
+    use insta::assert_debug_snapshot;
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn can_override_by_id() {
+        let temp_dir = TempDir::new("registry").unwrap();
+        let mut config = Config::with_custom_path(temp_dir.path());
+        fs::create_dir_all(&config.app_path).unwrap();
+        fs::write(
+            &config.sensitive_commands_path,
+            r###"
+- name: GitHub Env Token
+  test: GITHUB_TOKEN=(ghp_[0-9a-zA-Z]+)
+  secret_group: 1
+  id: github_env_token
+- name: Internal Token
+  test: INTERNAL_TOKEN=(.+)
+  secret_group: 1
+  id: internal_token
+"###,
+        )
+        .unwrap();
+        let project_path = temp_dir.path().join("project.yaml");
+        fs::write(
+            &project_path,
+            r###"
+- name: Project Internal Token
+  test: INTERNAL_TOKEN=(it_.+)
+  secret_group: 1
+  id: internal_token
+"###,
+        )
+        .unwrap();
+        config.system_patterns_path = temp_dir.path().join("not-found.yaml");
+        config.project_patterns_path = Some(project_path);
+
+        let registry = PatternRegistry::with_config(&config).unwrap();
+        let built_in = PatternRegistry::with_built_in().unwrap();
+
+        assert_debug_snapshot!(registry.patterns().len() == built_in.patterns().len() + 1);
+        assert_debug_snapshot!(registry
+            .patterns()
+            .iter()
+            .filter(|p| p.source != PatternSource::BuiltIn)
+            .map(|p| (
+                p.detection.id.clone(),
+                p.source,
+                p.overrides,
+                p.detection.test.to_string()
+            ))
+            .collect::<Vec<_>>());
+        temp_dir.close().unwrap();
+    }
+}
@@ -0,0 +1,432 @@
+use std::{collections::BTreeMap, fmt, fs, path::PathBuf};
+
+use anyhow::{anyhow, Result};
+use serde_derive::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use strum::{Display, EnumString, IntoEnumIterator};
+
+use crate::{config::Config, shell::Shell};
+
+/// prefix of the environment variables that override the settings, e.g.
+/// `SHELLCLEAR_MASK_PERCENTAGE`
+const ENV_PREFIX: &str = "SHELLCLEAR_";
+/// the mask percentage is a share of the secret length
+const MAX_MASK_PERCENTAGE: u8 = 100;
+
+/// Findings output format
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Display, EnumString, Deserialize, Serialize,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Table,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MaskSettings {
+    /// percentage of the secret to mask
+    pub percentage: u8,
+    /// secrets up to this length are masked completely
+    pub min_chars: usize,
+    /// text that replaces the masked characters
+    pub chars: String,
+}
+
+impl Default for MaskSettings {
+    fn default() -> Self {
+        Self {
+            percentage: 80,
+            min_chars: 3,
+            chars: "*".to_string(),
+        }
+    }
+}
+
+/// When findings of the same vendor, e.g. an AWS key id and its secret, are
+/// one credential incident
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorrelationSettings {
+    /// findings within this number of commands are correlated
+    pub max_distance: usize,
+    /// findings within this number of seconds are correlated, for histories
+    /// with timestamps
+    pub max_seconds: i64,
+}
+
+impl Default for CorrelationSettings {
+    fn default() -> Self {
+        Self {
+            max_distance: 3,
+            max_seconds: 300,
+        }
+    }
+}
+
+/// Default behaviour of `clear`, the `--remove` and `--backup` flags
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClearSettings {
+    /// remove the commands with findings instead of masking the secrets
+    pub remove: bool,
+    /// backup the history files before clearing them
+    pub backup: bool,
+}
+
+/// Where the effective value of a setting came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingOrigin {
+    Default,
+    /// `/etc/shellclear/config.yaml`
+    System(PathBuf),
+    /// `config.yaml` in `$XDG_CONFIG_HOME/shellclear`
+    User(PathBuf),
+    /// `config.yaml` in the `--config-dir` folder
+    ConfigDir(PathBuf),
+    /// `config.yaml` of the selected profile
+    Profile(PathBuf),
+    /// `SHELLCLEAR_*` environment variable
+    Env(String),
+}
+
+impl fmt::Display for SettingOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::System(path) => write!(f, "system {}", path.display()),
+            Self::User(path) => write!(f, "user {}", path.display()),
+            Self::ConfigDir(path) => write!(f, "config dir {}", path.display()),
+            Self::Profile(path) => write!(f, "profile {}", path.display()),
+            Self::Env(name) => write!(f, "env {name}"),
+        }
+    }
+}
+
+/// Settings of `config.yaml`, layered system < user < `--config-dir` <
+/// profile < `SHELLCLEAR_*` environment variables. command line flags
+/// override them
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    /// shells to scan, all the shells with a history file when empty
+    pub shells: Vec<String>,
+    /// default `find` output format
+    pub format: OutputFormat,
+    pub mask: MaskSettings,
+    pub correlation: CorrelationSettings,
+    pub clear: ClearSettings,
+    /// `find` exits with an error code when it reports findings
+    pub fail_on_findings: bool,
+    /// show the banner
+    pub banner: bool,
+    /// origin of every setting, by its dotted key, e.g. `mask.percentage`
+    #[serde(skip)]
+    pub origins: BTreeMap<String, SettingOrigin>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            shells: vec![],
+            format: OutputFormat::default(),
+            mask: MaskSettings::default(),
+            correlation: CorrelationSettings::default(),
+            clear: ClearSettings::default(),
+            fail_on_findings: false,
+            banner: true,
+            origins: BTreeMap::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// Load the settings files of the config and the environment variables
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` when a settings file or an environment variable is
+    /// invalid
+    pub fn load(config: &Config) -> Result<Self> {
+        let mut layers = vec![SettingOrigin::System(config.system_settings_path.clone())];
+        if let Some(path) = &config.user_settings_path {
+            layers.push(SettingOrigin::User(path.clone()));
+        }
+        if config.user_settings_path.as_ref() != Some(&config.settings_path) {
+            layers.push(SettingOrigin::ConfigDir(config.settings_path.clone()));
+        }
+        if let Some(path) = &config.profile_settings_path {
+            layers.push(SettingOrigin::Profile(path.clone()));
+        }
+        Self::from_layers(&layers, std::env::vars())
+    }
+
+    fn from_layers(
+        layers: &[SettingOrigin],
+        env: impl Iterator<Item = (String, String)>,
+    ) -> Result<Self> {
+        let mut values = BTreeMap::new();
+        flatten("", serde_yaml::to_value(Self::default())?, &mut values);
+        let mut values = values
+            .into_iter()
+            .map(|(key, value)| (key, (value, SettingOrigin::Default)))
+            .collect::<BTreeMap<_, _>>();
+
+        for origin in layers {
+            let (SettingOrigin::System(path)
+            | SettingOrigin::User(path)
+            | SettingOrigin::ConfigDir(path)
+            | SettingOrigin::Profile(path)) = origin
+            else {
+                continue;
+            };
+            if !path.exists() {
+                log::debug!("settings file not found: {}", path.display());
+                continue;
+            }
+            let content = fs::read_to_string(path)?;
+            // a file with only comments, e.g. a new profile, has no settings
+            if content
+                .lines()
+                .all(|l| l.trim().is_empty() || l.trim_start().starts_with('#'))
+            {
+                continue;
+            }
+            let content: Value = serde_yaml::from_str(&content)
+                .map_err(|e| anyhow!("invalid settings file {}. {}", path.display(), e))?;
+            let mut layer = BTreeMap::new();
+            flatten("", content, &mut layer);
+            for (key, value) in layer {
+                if !values.contains_key(&key) {
+                    return Err(anyhow!("unknown setting {} in {}", key, path.display()));
+                }
+                values.insert(key, (value, origin.clone()));
+            }
+        }
+
+        let env = env.collect::<BTreeMap<_, _>>();
+        for (key, (value, origin)) in &mut values {
+            let name = format!("{ENV_PREFIX}{}", key.to_uppercase().replace('.', "_"));
+            let Some(env_value) = env.get(&name) else {
+                continue;
+            };
+            *value = match value {
+                Value::Sequence(_) => Value::Sequence(
+                    env_value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|v| !v.is_empty())
+                        .map(|v| Value::String(v.to_string()))
+                        .collect(),
+                ),
+                Value::String(_) => Value::String(env_value.clone()),
+                _ => serde_yaml::from_str(env_value)
+                    .map_err(|e| anyhow!("invalid environment variable {}. {}", name, e))?,
+            };
+            *origin = SettingOrigin::Env(name);
+        }
+
+        let mut root = Mapping::new();
+        for (key, (value, _)) in &values {
+            insert(&mut root, key, value.clone());
+        }
+        let mut settings: Self = serde_yaml::from_value(Value::Mapping(root))
+            .map_err(|e| anyhow!("invalid settings. {}", e))?;
+        if let Some(shell) = settings
+            .shells
+            .iter()
+            .find(|name| !Shell::iter().any(|s| s.is_named(name)))
+        {
+            return Err(anyhow!("unknown shell {} in settings", shell));
+        }
+        if settings.mask.percentage > MAX_MASK_PERCENTAGE {
+            let origin = &values["mask.percentage"].1;
+            return Err(anyhow!(
+                "invalid setting mask.percentage {} in {}, the percentage must be at most {}",
+                settings.mask.percentage,
+                origin,
+                MAX_MASK_PERCENTAGE
+            ));
+        }
+        settings.origins = values
+            .into_iter()
+            .map(|(key, (_, origin))| (key, origin))
+            .collect();
+        Ok(settings)
+    }
+
+    /// Is the shell one of the shells to scan
+    #[must_use]
+    pub fn scans(&self, shell: &Shell) -> bool {
+        self.shells.is_empty() || self.shells.iter().any(|name| shell.is_named(name))
+    }
+
+    /// Effective value and origin of every setting, by its dotted key
+    #[must_use]
+    pub fn entries(&self) -> Vec<(String, String, SettingOrigin)> {
+        let mut values = BTreeMap::new();
+        if let Ok(value) = serde_yaml::to_value(self) {
+            flatten("", value, &mut values);
+        }
+        values
+            .into_iter()
+            .map(|(key, value)| {
+                let origin = self
+                    .origins
+                    .get(&key)
+                    .cloned()
+                    .unwrap_or(SettingOrigin::Default);
+                (key, render(&value), origin)
+            })
+            .collect()
+    }
+}
+
+/// One line YAML of a setting value, e.g. `[bash, zsh]`
+fn render(value: &Value) -> String {
+    match value {
+        Value::Sequence(values) => format!(
+            "[{}]",
+            values.iter().map(render).collect::<Vec<_>>().join(", ")
+        ),
+        value => serde_yaml::to_string(value)
+            .unwrap_or_default()
+            .trim_start_matches("---")
+            .trim()
+            .to_string(),
+    }
+}
+
+/// Flatten nested mappings to dotted keys, e.g. `mask.percentage`
+fn flatten(prefix: &str, value: Value, values: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Mapping(mapping) => {
+            for (key, value) in mapping {
+                let key = key.as_str().map_or_else(
+                    || serde_yaml::to_string(&key).unwrap_or_default(),
+                    ToString::to_string,
+                );
+                let key = if prefix.is_empty() {
+                    key
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten(&key, value, values);
+            }
+        }
+        Value::Null if prefix.is_empty() => {}
+        value => {
+            values.insert(prefix.to_string(), value);
+        }
+    }
+}
+
+fn insert(mapping: &mut Mapping, key: &str, value: Value) {
+    match key.split_once('.') {
+        None => {
+            mapping.insert(Value::String(key.to_string()), value);
+        }
+        Some((first, rest)) => {
+            let entry = mapping
+                .entry(Value::String(first.to_string()))
+                .or_insert_with(|| Value::Mapping(Mapping::new()));
+            if let Value::Mapping(inner) = entry {
+                insert(inner, rest, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_settings {
+    use insta::assert_debug_snapshot;
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn can_layer_settings() {
+        let temp_dir = TempDir::new("settings").unwrap();
+        let system = temp_dir.path().join("system.yaml");
+        let user = temp_dir.path().join("user.yaml");
+        let config_dir = temp_dir.path().join("config-dir.yaml");
+        fs::write(
+            &system,
+            "format: table\nbanner: false\nmask:\n  percentage: 50\n",
+        )
+        .unwrap();
+        fs::write(&user, "mask:\n  chars: '#'\n").unwrap();
+        let profile = temp_dir.path().join("profile.yaml");
+        fs::write(&config_dir, "banner: true\n").unwrap();
+        fs::write(
+            &profile,
+            "clear:\n  remove: true\nmask:\n  percentage: 100\n",
+        )
+        .unwrap();
+
+        let settings = Settings::from_layers(
+            &[
+                SettingOrigin::System(system),
+                SettingOrigin::User(user),
+                SettingOrigin::ConfigDir(config_dir),
+                SettingOrigin::Profile(profile),
+            ],
+            vec![
+                ("SHELLCLEAR_MASK_PERCENTAGE".to_string(), "90".to_string()),
+                ("SHELLCLEAR_SHELLS".to_string(), "bash, zsh".to_string()),
+            ]
+            .into_iter(),
+        )
+        .unwrap();
+
+        assert_debug_snapshot!(settings
+            .entries()
+            .into_iter()
+            .map(|(key, value, origin)| format!(
+                "{key}: {value} ({})",
+                origin
+                    .to_string()
+                    .replace(&temp_dir.path().display().to_string(), "")
+            ))
+            .collect::<Vec<_>>());
+        assert_debug_snapshot!((settings.scans(&Shell::Zshrc), settings.scans(&Shell::Fish)));
+        temp_dir.close().unwrap();
+    }
+
+    #[test]
+    fn can_reject_invalid_settings() {
+        let temp_dir = TempDir::new("settings-invalid").unwrap();
+        let path = temp_dir.path().join("config.yaml");
+        fs::write(&path, "colors: true\n").unwrap();
+
+        let unknown_key =
+            Settings::from_layers(&[SettingOrigin::User(path.clone())], std::iter::empty())
+                .is_err();
+        let unknown_shell = Settings::from_layers(
+            &[],
+            vec![("SHELLCLEAR_SHELLS".to_string(), "tcsh".to_string())].into_iter(),
+        )
+        .is_err();
+        let invalid_format = Settings::from_layers(
+            &[],
+            vec![("SHELLCLEAR_FORMAT".to_string(), "html".to_string())].into_iter(),
+        )
+        .is_err();
+        assert_debug_snapshot!((unknown_key, unknown_shell, invalid_format));
+
+        fs::write(&path, "mask:\n  percentage: 101\n").unwrap();
+        let file_percentage =
+            Settings::from_layers(&[SettingOrigin::User(path.clone())], std::iter::empty())
+                .map_err(|e| e.to_string().replace(&path.display().to_string(), "PATH"));
+        let env_percentage = Settings::from_layers(
+            &[],
+            vec![("SHELLCLEAR_MASK_PERCENTAGE".to_string(), "150".to_string())].into_iter(),
+        )
+        .map_err(|e| e.to_string());
+        assert_debug_snapshot!((file_percentage.err(), env_percentage.err()));
+        temp_dir.close().unwrap();
+    }
+}
@@ -3,6 +3,8 @@ use std::{fmt, path::Path};
 use serde_derive::{Deserialize, Serialize};
 use strum::{EnumIter, IntoEnumIterator};
 
+use crate::state;
+
 /// List of all supported shells
 #[derive(Debug, EnumIter, Clone, Eq, Hash, PartialEq)]
 pub enum Shell {
@@ -29,6 +31,15 @@ impl fmt::Display for Shell {
     }
 }
 
+impl Shell {
+    /// Is the shell called by the name, `zsh` is an alias of `zshrc`
+    #[must_use]
+    pub fn is_named(&self, name: &str) -> bool {
+        name.eq_ignore_ascii_case(&self.to_string())
+            || (name.eq_ignore_ascii_case("zsh") && *self == Self::Zshrc)
+    }
+}
+
 /// Zsh history file name
 const ZSH_HISTORY_FILE_PATH: &str = ".zsh_history";
 /// Bash history file name
@@ -48,13 +59,31 @@ pub struct History {
     pub file_name: String,
 }
 
-/// return list of all existing history files
+/// return list of all existing history files. inside a root the history
+/// paths are resolved in the root and only regular files are returned
 #[must_use]
-pub fn get_all_history_files(homedir: &str) -> Vec<History> {
+pub fn get_all_history_files(homedir: &str, root: Option<&Path>) -> Vec<History> {
     // return list of existing shells
     Shell::iter()
         .filter_map(|shell| {
             let shell_history_path = get_shell_history_path(&shell, homedir);
+            let shell_history_path = match root {
+                Some(root) => {
+                    let relative = Path::new(&shell_history_path).strip_prefix(root).ok()?;
+                    match state::resolve_in_root(root, relative) {
+                        Ok(path) if path.is_file() => path.display().to_string(),
+                        Ok(_) => {
+                            log::debug!("shell {:?} not found", shell);
+                            return None;
+                        }
+                        Err(e) => {
+                            log::debug!("could not resolve shell {:?} history. err: {}", shell, e);
+                            return None;
+                        }
+                    }
+                }
+                None => shell_history_path,
+            };
             if !Path::new(&shell_history_path).exists() {
                 log::debug!("shell {:?} not found", shell);
                 return None;
@@ -121,7 +150,7 @@ mod state_shell {
             (r"//*.+/(app)", "PATH"),
             (r"([C]?:\\.+app\\\\)", "PATH/")// for windows
         ]}, {
-            assert_debug_snapshot!(get_all_history_files(&temp_dir.display().to_string()));
+            assert_debug_snapshot!(get_all_history_files(&temp_dir.display().to_string(), None));
         });
     }
 }
@@ -1,6 +1,12 @@
 extern crate chrono;
 
-use std::{fs, fs::write, path::Path};
+use std::{
+    ffi::OsString,
+    fs,
+    fs::write,
+    io,
+    path::{Component, Path, PathBuf},
+};
 
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Local};
@@ -10,13 +16,12 @@ use crate::shell;
 
 /// timestamp format for attached backups file.
 const DATE_TIME_BACKUP_FORMAT: &str = "%Y%m%d%H%M%S%.f";
-/// the base folder name for the crates to store the data like backups, pop
-/// files etc.
-const STATE_FOLDER_NAME: &str = ".shellclear";
 /// backup folder name
-const BACKUP_FOLDER: &str = "backups";
+pub(crate) const BACKUP_FOLDER: &str = "backups";
 /// stash folder name
-const STASH_FOLDER: &str = "stash";
+pub(crate) const STASH_FOLDER: &str = "stash";
+/// symlinks followed when resolving a path inside a root, like `MAXSYMLINKS`
+const MAX_SYMLINKS: usize = 40;
 
 /// describe the shell context which contain the app folder path and the shell
 /// history details
@@ -24,44 +29,223 @@ const STASH_FOLDER: &str = "stash";
 pub struct ShellContext {
     pub app_folder_path: String,
     pub history: shell::History,
+    /// owner of the scanned home, with `--all-users`
+    pub user: Option<String>,
+    /// the scanned root, with `--root`. the history file is never written
+    /// through a symlink inside it
+    pub root: Option<PathBuf>,
+}
+
+/// A user of a passwd file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct User {
+    pub name: String,
+    pub home: PathBuf,
+}
+
+/// Read the users of a passwd file, `name:password:uid:gid:gecos:home:shell`
+///
+/// # Errors
+///
+/// Will return `Err` when the file could not be read
+pub fn read_passwd(path: &Path) -> Result<Vec<User>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("could not read passwd file {}: {}", path.display(), e))?;
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let fields = line.split(':').collect::<Vec<_>>();
+            if fields.len() < 7 || fields[0].is_empty() || fields[5].is_empty() {
+                debug!("skip invalid passwd line: {}", line);
+                return None;
+            }
+            Some(User {
+                name: fields[0].to_string(),
+                home: PathBuf::from(fields[5]),
+            })
+        })
+        .collect())
+}
+
+/// Resolve the home directory to scan: the given home or the home of the
+/// current user, inside the given root, e.g. a mounted disk image or a
+/// container rootfs
+///
+/// # Errors
+///
+/// Will return `Err` when the home directory is not found
+pub fn resolve_home(home: Option<&Path>, root: Option<&Path>) -> Result<PathBuf> {
+    let home = match home {
+        Some(h) => h.to_path_buf(),
+        None => dirs::home_dir().ok_or_else(|| anyhow!("could not get directory path"))?,
+    };
+    let home = match root {
+        Some(root) => resolve_in_root(root, &home)?,
+        None => home,
+    };
+    if !home.is_dir() {
+        return Err(anyhow!("home directory {} not found", home.display()));
+    }
+    Ok(home)
+}
+
+/// Resolve the path inside the root like in a chroot: `..` stops at the root
+/// and every component is checked for symlinks, an absolute link target is
+/// resolved from the root. the resolved path has no symlinks, so it can't
+/// escape the root. the missing components are kept as is
+///
+/// # Errors
+///
+/// Will return `Err` when a symlink could not be read or there are too many
+/// symlinks, e.g. a loop
+pub fn resolve_in_root(root: &Path, path: &Path) -> Result<PathBuf> {
+    fn push_components(pending: &mut Vec<OsString>, path: &Path) {
+        for c in path.components().rev() {
+            match c {
+                Component::Normal(part) => pending.push(part.to_os_string()),
+                Component::ParentDir => pending.push(OsString::from("..")),
+                _ => {}
+            }
+        }
+    }
+
+    let mut pending = vec![];
+    push_components(&mut pending, path);
+    let mut resolved = root.to_path_buf();
+    let mut links = 0;
+    while let Some(part) = pending.pop() {
+        if part == ".." {
+            if resolved != root {
+                resolved.pop();
+            }
+            continue;
+        }
+        let current = resolved.join(&part);
+        match fs::symlink_metadata(&current) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                links += 1;
+                if links > MAX_SYMLINKS {
+                    return Err(anyhow!("too many symlinks in {}", path.display()));
+                }
+                let target = fs::read_link(&current)?;
+                if target.has_root() {
+                    resolved = root.to_path_buf();
+                }
+                push_components(&mut pending, &target);
+            }
+            _ => resolved = current,
+        }
+    }
+    Ok(resolved)
 }
 
 /// Init crates state folder for storing history data and detect all history
-/// shell files
+/// shell files of the home directory
 ///
 /// # Errors
 ///
 /// Will return `Err` when has en create a dir problem
-pub fn init() -> Result<Vec<ShellContext>> {
-    let homedir = match dirs::home_dir() {
-        Some(h) => h.display().to_string(),
-        None => return Err(anyhow!("could not get directory path")),
-    };
+pub fn init(state_path: &Path, home: &Path, root: Option<&Path>) -> Result<Vec<ShellContext>> {
+    let homedir = home.display().to_string();
 
     // create a application folder to save all the temp data
-    let state_folder = Path::new(&homedir)
-        .join(STATE_FOLDER_NAME)
-        .display()
-        .to_string();
-
-    if let Err(err) = fs::create_dir(&state_folder) {
-        if err.kind() != std::io::ErrorKind::AlreadyExists {
-            return Err(anyhow!("could not create folder: {}", err));
-        }
-        debug!("state folder found: {:?}", state_folder);
-    } else {
-        debug!("state created in path: {:?}", state_folder);
+    let state_folder = state_path.display().to_string();
+
+    if let Err(err) = fs::create_dir_all(&state_folder) {
+        return Err(anyhow!("could not create folder: {}", err));
     }
+    debug!("state folder: {:?}", state_folder);
 
-    Ok(shell::get_all_history_files(&homedir)
+    Ok(shell::get_all_history_files(&homedir, root)
         .iter()
         .map(|h| ShellContext {
             app_folder_path: state_folder.clone(),
             history: h.clone(),
+            user: None,
+            root: root.map(Path::to_path_buf),
         })
         .collect::<Vec<_>>())
 }
 
+/// Init crates state folder and detect the history shell files of every user
+/// of the passwd file, resolved inside the given root. users without a home
+/// directory are skipped, and a home shared by several users is scanned once
+///
+/// # Errors
+///
+/// Will return `Err` when the passwd file could not be read or has en create
+/// a dir problem
+pub fn init_all_users(
+    state_path: &Path,
+    passwd: &Path,
+    root: Option<&Path>,
+) -> Result<Vec<ShellContext>> {
+    let mut homes = vec![];
+    let mut contexts = vec![];
+    for user in read_passwd(passwd)? {
+        let home = match resolve_home(Some(&user.home), root) {
+            Ok(home) => home,
+            Err(e) => {
+                debug!("skip user {}: {}", user.name, e);
+                continue;
+            }
+        };
+        if homes.contains(&home) {
+            debug!("skip user {}: home already scanned", user.name);
+            continue;
+        }
+        contexts.extend(
+            init(state_path, &home, root)?
+                .into_iter()
+                .map(|c| ShellContext {
+                    user: Some(user.name.clone()),
+                    ..c
+                }),
+        );
+        homes.push(home);
+    }
+    Ok(contexts)
+}
+
+/// Split the shell contexts by user, in order. the findings of a user are
+/// kept apart because the history files of the same shell are merged
+#[must_use]
+pub fn group_by_user(shells_context: &[ShellContext]) -> Vec<(Option<String>, Vec<ShellContext>)> {
+    let mut groups: Vec<(Option<String>, Vec<ShellContext>)> = vec![];
+    for context in shells_context {
+        match groups.iter_mut().find(|(user, _)| user == &context.user) {
+            Some((_, contexts)) => contexts.push(context.clone()),
+            None => groups.push((context.user.clone(), vec![context.clone()])),
+        }
+    }
+    groups
+}
+
+/// Run the file operation and restore the owner, group and mode the file had
+/// before it, so a history file rewritten as root keeps its owner
+///
+/// # Errors
+///
+/// Will return `Err` when the operation fails or the owner could not be
+/// restored
+pub fn preserve_owner<T>(path: &Path, operation: impl FnOnce() -> Result<T>) -> Result<T> {
+    let metadata = fs::metadata(path).ok();
+    let result = operation()?;
+    if let Some(metadata) = metadata {
+        fs::set_permissions(path, metadata.permissions())?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let current = fs::metadata(path)?;
+            if current.uid() != metadata.uid() || current.gid() != metadata.gid() {
+                std::os::unix::fs::chown(path, Some(metadata.uid()), Some(metadata.gid()))?;
+            }
+        }
+    }
+    Ok(result)
+}
+
 impl ShellContext {
     /// backup history shell file to backup folder
     ///
@@ -97,8 +281,11 @@ impl ShellContext {
     ///
     /// Will return `Err` when copy history file is fails
     pub fn restore(&self, file_path: &str) -> Result<()> {
+        self.check_writable()?;
         debug!("restore file: {} to: {}", file_path, &self.history.path);
-        fs::copy(file_path, &self.history.path)?;
+        preserve_owner(Path::new(&self.history.path), || {
+            Ok(fs::copy(file_path, &self.history.path)?)
+        })?;
         debug!(
             "restore successfully file: {} to: {}",
             file_path, &self.history.path
@@ -112,8 +299,11 @@ impl ShellContext {
     ///
     /// Will return `Err` when save history content
     pub fn save_history_content(&self, content: &str) -> Result<()> {
+        self.check_writable()?;
         debug!("save history content in path: {:}", &self.history.path);
-        write(&self.history.path, content)?;
+        preserve_owner(Path::new(&self.history.path), || {
+            Ok(write(&self.history.path, content)?)
+        })?;
         debug!(
             "save successfully history content in path: {:}",
             &self.history.path
@@ -136,12 +326,15 @@ impl ShellContext {
             debug!("history path not found: {}", self.history.path);
             return Err(anyhow!("history path {} not found", self.history.path));
         }
+        self.check_writable()?;
         // create backup folder
         fs::create_dir_all(self.get_stash_folder())?;
         debug!("stash file: {} to: {}", &self.history.path, copy_to);
         fs::copy(&self.history.path, &copy_to)?;
         debug!("remove file: {}", self.history.path);
-        fs::File::create(&self.history.path)?;
+        preserve_owner(Path::new(&self.history.path), || {
+            Ok(fs::File::create(&self.history.path)?)
+        })?;
         debug!("remove successfully file: {}", self.history.path);
         Ok(copy_to)
     }
@@ -157,8 +350,11 @@ impl ShellContext {
             .display()
             .to_string();
 
+        self.check_writable()?;
         debug!("pop file: {} to: {}", &copy_from, &self.history.path);
-        fs::copy(&copy_from, &self.history.path)?;
+        preserve_owner(Path::new(&self.history.path), || {
+            Ok(fs::copy(&copy_from, &self.history.path)?)
+        })?;
         fs::remove_file(&copy_from)?;
         debug!("remove successfully file: {}", copy_from);
         Ok(self.history.path.to_string())
@@ -222,10 +418,28 @@ impl ShellContext {
         Ok(count >= 1)
     }
 
+    /// Refuse to write the history path when it is not a regular file, e.g. a
+    /// fifo or a device. inside a root a symlink is refused too, the link of an
+    /// untrusted disk image could point to any file of the host
+    fn check_writable(&self) -> Result<()> {
+        let metadata = if self.root.is_some() {
+            fs::symlink_metadata(&self.history.path)
+        } else {
+            fs::metadata(&self.history.path)
+        };
+        match metadata {
+            Ok(metadata) if !metadata.file_type().is_file() => Err(anyhow!(
+                "history path {} is not a regular file",
+                self.history.path
+            )),
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
     /// return a backup folder path
     fn get_backup_folder(&self) -> String {
-        Path::new(&self.app_folder_path)
-            .join(BACKUP_FOLDER)
+        self.get_user_folder(BACKUP_FOLDER)
             .join(self.history.shell.to_string())
             .display()
             .to_string()
@@ -233,10 +447,17 @@ impl ShellContext {
 
     /// return stash folder
     fn get_stash_folder(&self) -> String {
-        Path::new(&self.app_folder_path)
-            .join(STASH_FOLDER)
-            .display()
-            .to_string()
+        self.get_user_folder(STASH_FOLDER).display().to_string()
+    }
+
+    /// return the state folder of the user, the state folder itself without
+    /// a user
+    fn get_user_folder(&self, folder: &str) -> PathBuf {
+        let path = Path::new(&self.app_folder_path).join(folder);
+        match &self.user {
+            Some(user) => path.join(user),
+            None => path,
+        }
     }
 }
 
@@ -247,7 +468,7 @@ mod state_context {
     use insta::assert_debug_snapshot;
     use tempdir::TempDir;
 
-    use super::{fs, shell, Path, ShellContext};
+    use super::{fs, group_by_user, init, init_all_users, resolve_home, shell, Path, ShellContext};
     use crate::shell::Shell;
 
     const TEMP_HISTORY_CONTENT: &str = "history
@@ -274,6 +495,8 @@ export GITHUB_TOKEN=token
                 path: history_file_path.display().to_string(),
                 file_name: history_file_name.to_string(),
             },
+            user: None,
+            root: None,
         }
     }
 
@@ -367,6 +590,134 @@ export GITHUB_TOKEN=token
         temp_dir.close().unwrap();
     }
 
+    #[test]
+    fn can_resolve_home_in_root() {
+        let temp_dir = TempDir::new("terminal").unwrap();
+        let root = temp_dir.path().join("rootfs");
+        fs::create_dir_all(root.join("home/alice")).unwrap();
+
+        let resolve = |home: &str| {
+            resolve_home(Some(Path::new(home)), Some(&root)).map(|p| {
+                p.strip_prefix(temp_dir.path())
+                    .unwrap()
+                    .display()
+                    .to_string()
+            })
+        };
+        assert_debug_snapshot!(resolve("/home/alice").unwrap());
+        assert_debug_snapshot!(resolve("/home/../../home/alice").unwrap());
+        assert_debug_snapshot!(resolve("/home/bob").is_err());
+        temp_dir.close().unwrap();
+    }
+
+    #[test]
+    fn can_init_all_users() {
+        let temp_dir = TempDir::new("terminal").unwrap();
+        let root = temp_dir.path().join("rootfs");
+        for home in ["root", "home/alice", "home/bob"] {
+            fs::create_dir_all(root.join(home)).unwrap();
+            fs::write(root.join(home).join(".bash_history"), "ls\n").unwrap();
+        }
+        fs::write(root.join("home/alice/.zsh_history"), "ls\n").unwrap();
+        let passwd = temp_dir.path().join("passwd");
+        fs::write(
+            &passwd,
+            r###"# users
+root:x:0:0:root:/root:/bin/bash
+daemon:x:1:1:daemon:/usr/sbin:/usr/sbin/nologin
+alice:x:1000:1000:Alice,,,:/home/alice:/bin/zsh
+alice2:x:1001:1001::/home/alice:/bin/zsh
+invalid line
+bob:x:1002:1002::/home/bob:/bin/bash
+"###,
+        )
+        .unwrap();
+
+        let contexts =
+            init_all_users(&temp_dir.path().join("state"), &passwd, Some(&root)).unwrap();
+        assert_debug_snapshot!(group_by_user(&contexts)
+            .iter()
+            .map(|(user, contexts)| (
+                user.clone(),
+                contexts
+                    .iter()
+                    .map(|c| c.history.shell.to_string())
+                    .collect::<Vec<_>>()
+            ))
+            .collect::<Vec<_>>());
+        assert_debug_snapshot!(contexts[0]
+            .get_stash_folder()
+            .replace(&temp_dir.path().display().to_string(), ""));
+        temp_dir.close().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn can_keep_absolute_symlinks_in_root() {
+        let temp_dir = TempDir::new("terminal").unwrap();
+        let root = temp_dir.path().join("rootfs");
+        let host_file = temp_dir.path().join("host/secret_file");
+        fs::create_dir_all(root.join("home/u")).unwrap();
+        fs::create_dir_all(host_file.parent().unwrap()).unwrap();
+        fs::write(&host_file, "export GITHUB_TOKEN=host\n").unwrap();
+        std::os::unix::fs::symlink(&host_file, root.join("home/u/.bash_history")).unwrap();
+        std::os::unix::fs::symlink("/home/u", root.join("home/link")).unwrap();
+        let state = temp_dir.path().join("state");
+        let relative = |path: &Path| {
+            path.strip_prefix(temp_dir.path())
+                .unwrap()
+                .display()
+                .to_string()
+        };
+
+        // the link target is resolved in the root, where it doesn't exist
+        let home = resolve_home(Some(Path::new("/home/link")), Some(&root)).unwrap();
+        assert_debug_snapshot!(relative(&home));
+        assert_debug_snapshot!(init(&state, &home, Some(&root)).unwrap().len());
+
+        let rerooted = root.join(host_file.strip_prefix("/").unwrap());
+        fs::create_dir_all(rerooted.parent().unwrap()).unwrap();
+        fs::write(&rerooted, "export GITHUB_TOKEN=image\n").unwrap();
+        let contexts = init(&state, &home, Some(&root)).unwrap();
+        assert_eq!(Path::new(&contexts[0].history.path), rerooted);
+
+        // a history path that is a symlink is never written inside a root
+        let context = ShellContext {
+            history: shell::History {
+                path: root.join("home/u/.bash_history").display().to_string(),
+                ..contexts[0].history.clone()
+            },
+            ..contexts[0].clone()
+        };
+        assert_debug_snapshot!(context.save_history_content("").is_err());
+        assert_debug_snapshot!(context.stash().is_err());
+        assert_debug_snapshot!(fs::read_to_string(&host_file).unwrap());
+        temp_dir.close().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn can_preserve_mode_on_save() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new("terminal").unwrap();
+        let context = create_mock_state(&temp_dir);
+        fs::set_permissions(&context.history.path, fs::Permissions::from_mode(0o600)).unwrap();
+
+        context.save_history_content("new commands").unwrap();
+        context.stash().unwrap();
+        context.pop().unwrap();
+        assert_debug_snapshot!(format!(
+            "{:o}",
+            fs::metadata(&context.history.path)
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o777
+        ));
+        temp_dir.close().unwrap();
+    }
+
     #[test]
     fn can_get_stash_file() {
         let temp_dir = TempDir::new("terminal").unwrap();
@@ -0,0 +1,87 @@
+use crate::data::Detection;
+
+/// Result of running an example or a counter example of a pattern
+#[derive(Debug)]
+pub struct ExampleResult {
+    pub pattern_id: String,
+    pub text: String,
+    /// expected secret, `None` for a counter example
+    pub expected: Option<String>,
+    /// secret captured by the pattern, `None` when the pattern doesn't match
+    pub captured: Option<String>,
+}
+
+impl ExampleResult {
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.expected == self.captured
+    }
+}
+
+/// Run the examples and counter examples of the pattern
+#[must_use]
+pub fn run_examples(detection: &Detection) -> Vec<ExampleResult> {
+    let capture = |text: &str| {
+        detection
+            .test
+            .captures(text)
+            .filter(|_| detection.has_keyword(text))
+            .and_then(|c| c.get(detection.secret_group as usize))
+            .map(|m| m.as_str().to_string())
+            .filter(|secret| detection.has_min_entropy(secret))
+    };
+    let examples = detection.examples.iter().map(|e| ExampleResult {
+        pattern_id: detection.id.clone(),
+        text: e.text.clone(),
+        expected: Some(e.secret.clone()),
+        captured: capture(&e.text),
+    });
+    let counter_examples = detection.counter_examples.iter().map(|text| ExampleResult {
+        pattern_id: detection.id.clone(),
+        text: text.clone(),
+        expected: None,
+        captured: capture(text),
+    });
+    examples.chain(counter_examples).collect()
+}
+
+#[cfg(test)]
+mod test_tester {
+    use insta::assert_debug_snapshot;
+
+    use super::*;
+    use crate::engine::SENSITIVE_COMMANDS;
+
+    #[test]
+    fn can_run_examples() {
+        let patterns: Vec<Detection> = serde_yaml::from_str(
+            r###"
+- id: my_token
+  name: My Token
+  test: token=(\w+)
+  secret_group: 1
+  examples:
+    - text: curl -H token=abc
+      secret: abc
+    - text: curl -H token=abc-def
+      secret: abc-def
+  counter_examples:
+    - curl -H token=
+    - curl -H token=xyz
+"###,
+        )
+        .unwrap();
+        assert_debug_snapshot!(run_examples(&patterns[0]));
+    }
+
+    #[test]
+    fn built_in_examples_pass() {
+        let patterns: Vec<Detection> = serde_yaml::from_str(SENSITIVE_COMMANDS).unwrap();
+        let failed = patterns
+            .iter()
+            .flat_map(run_examples)
+            .filter(|r| !r.passed())
+            .collect::<Vec<_>>();
+        assert_debug_snapshot!(failed);
+    }
+}
@@ -27,10 +27,7 @@ fn can_detect_regex_sensitive_patterns() {
         let pattern_file = format!("tests/suites_sensitive_patterns/{}.yaml", pattern_id);
         let f = match std::fs::File::open(pattern_file) {
             Ok(f) => f,
-            Err(_e) => {
-                println!("not found tests for {}", pattern.id);
-                return assert!(false);
-            }
+            Err(_e) => panic!("not found tests for {}", pattern.id),
         };
         let tests: Vec<TestSensitivePatterns> = serde_yaml::from_reader(f).unwrap();
         let mut results = Vec::new();